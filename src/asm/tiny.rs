@@ -1,22 +1,21 @@
 //! Tiny Assembly - https://engineering.purdue.edu/~milind/ece468/2017fall/assignments/step4/tinyDoc.txt
-use std::collections::HashMap;
-use std::sync::atomic::{AtomicU64, Ordering};
-
 use crate::register_alloc::types::{RegisterAllocatedThreeAddressCode, RegisterId, SpillType};
-use crate::symbol_table::symbol::data::{FunctionScopedSymbol, NonFunctionScopedSymbol, Symbol};
+use crate::symbol_table::symbol::data::{NonFunctionScopedSymbol, NumValue, Symbol};
 use crate::symbol_table::symbol::{data, function};
 use crate::symbol_table::SymbolTable;
 use crate::three_addr_code_ir;
 use crate::three_addr_code_ir::three_address_code::ThreeAddressCode;
-use crate::three_addr_code_ir::{
-    LValue, LValueF, LValueI, RValueF, RValueI, ResultType, TempF, TempI,
-};
-use atomic_refcell::AtomicRefCell;
+use crate::three_addr_code_ir::{IdentA, RValueF, RValueI, ResultType};
 use std::fmt::Formatter;
+use std::ops::Range;
 use std::rc::Rc;
 
 pub const ALLOWED_REGISTERS: usize = 4;
 
+/// Name of the string written out before halting
+/// on an out of bounds array element access.
+const ARRAY_INDEX_ERROR: &str = "array_index_error";
+
 #[derive(Debug, Copy, Clone, derive_more::Display)]
 #[display(fmt = "label{}", _0)]
 pub struct Label(usize);
@@ -49,6 +48,14 @@ impl From<&RegisterId> for Register {
 pub enum Opmr {
     Reg(Register),
     Id(data::Symbol),
+    /// Element of an array at a fixed index.
+    Element(data::Symbol, usize),
+}
+
+/// Returns the name of the memory id that holds
+/// an element of a global array.
+fn global_element_name(array_name: &str, element: usize) -> String {
+    format!("{}[{}]", array_name, element)
 }
 
 // Using hand written impl for display for Opmr
@@ -75,6 +82,10 @@ impl std::fmt::Display for Opmr {
                             | data::FunctionScopedSymbol::Float {
                                 symbol_type: data::FunctionScopedSymbolType::Parameter(num_params),
                                 index,
+                            }
+                            | data::FunctionScopedSymbol::Bool {
+                                symbol_type: data::FunctionScopedSymbolType::Parameter(num_params),
+                                index,
                             } => {
                                 write!(
                                     f,
@@ -85,13 +96,38 @@ impl std::fmt::Display for Opmr {
                                 )
                             }
                             data::FunctionScopedSymbol::Int { index, .. }
-                            | data::FunctionScopedSymbol::Float { index, .. } => {
+                            | data::FunctionScopedSymbol::Float { index, .. }
+                            | data::FunctionScopedSymbol::Bool { index, .. }
+                            | data::FunctionScopedSymbol::IntArray { index, .. }
+                            | data::FunctionScopedSymbol::FloatArray { index, .. } => {
                                 write!(f, "$-{}", index)
                             }
+                            data::FunctionScopedSymbol::Record { .. } => unreachable!(
+                                "Tried to access RECORD symbol [{}] rather than one of its fields.",
+                                symbol
+                            ),
                         }
                     }
                 }
             }
+            // Elements of global arrays are laid out as one memory id
+            // per element, while elements of local arrays take up
+            // consecutive stack slots starting at the array's index.
+            Opmr::Element(array, element) => match array {
+                Symbol::NonFunctionScopedSymbol(symbol) => {
+                    write!(f, "{}", global_element_name(symbol.name(), *element))
+                }
+                Symbol::FunctionScopedSymbol(symbol) => match **symbol {
+                    data::FunctionScopedSymbol::IntArray { index, .. }
+                    | data::FunctionScopedSymbol::FloatArray { index, .. } => {
+                        write!(f, "$-{}", index + element)
+                    }
+                    _ => unreachable!(
+                        "Tried to access an element of non-array symbol: [{}].",
+                        symbol
+                    ),
+                },
+            },
         }
     }
 }
@@ -212,6 +248,165 @@ pub enum TinyCode {
     End,
 }
 
+/// Generates code to access the element of `array` at the index
+/// held in `index_reg`. Tiny has no indirect addressing, so the
+/// index is narrowed down by a binary search over the indices of
+/// the array, which ends in an access to the matching element at
+/// a fixed location. `access` generates the code for that access.
+/// With the `array-bounds-checks` feature an out of bounds index
+/// writes out an error and halts the program.
+fn gen_element_access(
+    array: IdentA,
+    index_reg: Register,
+    access: impl Fn(Opmr) -> TinyCode,
+) -> Vec<TinyCode> {
+    let size = array.size();
+    let done_label = three_addr_code_ir::Label::new().into();
+    let mut code_sequence = vec![];
+
+    if cfg!(feature = "array-bounds-checks") {
+        let out_of_bounds_label = three_addr_code_ir::Label::new().into();
+
+        // 0 > index
+        code_sequence.push(TinyCode::CmpI(OpmrIL::Literal(0), index_reg));
+        code_sequence.push(TinyCode::Jgt(out_of_bounds_label));
+
+        // size <= index
+        code_sequence.push(TinyCode::CmpI(OpmrIL::Literal(size as i32), index_reg));
+        code_sequence.push(TinyCode::Jle(out_of_bounds_label));
+
+        gen_element_dispatch(
+            &array.0,
+            0..size,
+            index_reg,
+            done_label,
+            &access,
+            &mut code_sequence,
+        );
+
+        code_sequence.push(TinyCode::Label(out_of_bounds_label));
+        code_sequence.push(TinyCode::WriteS(data::Symbol::NonFunctionScopedSymbol(
+            Rc::new(NonFunctionScopedSymbol::String {
+                name: ARRAY_INDEX_ERROR.to_owned(),
+                value: String::new(),
+            }),
+        )));
+        code_sequence.push(TinyCode::Halt);
+    } else {
+        gen_element_dispatch(
+            &array.0,
+            0..size,
+            index_reg,
+            done_label,
+            &access,
+            &mut code_sequence,
+        );
+    }
+
+    code_sequence.push(TinyCode::Label(done_label));
+    code_sequence
+}
+
+/// Generates the binary search over the element indices in
+/// `elements`, for `gen_element_access`.
+fn gen_element_dispatch(
+    array: &data::Symbol,
+    elements: Range<usize>,
+    index_reg: Register,
+    done_label: Label,
+    access: &impl Fn(Opmr) -> TinyCode,
+    code_sequence: &mut Vec<TinyCode>,
+) {
+    if elements.len() == 1 {
+        code_sequence.push(access(Opmr::Element(array.clone(), elements.start)));
+        code_sequence.push(TinyCode::Jmp(done_label));
+        return;
+    }
+
+    let mid = elements.start + elements.len() / 2;
+    let upper_half_label = three_addr_code_ir::Label::new().into();
+
+    // mid <= index
+    code_sequence.push(TinyCode::CmpI(OpmrIL::Literal(mid as i32), index_reg));
+    code_sequence.push(TinyCode::Jle(upper_half_label));
+
+    gen_element_dispatch(
+        array,
+        elements.start..mid,
+        index_reg,
+        done_label,
+        access,
+        code_sequence,
+    );
+
+    code_sequence.push(TinyCode::Label(upper_half_label));
+    gen_element_dispatch(
+        array,
+        mid..elements.end,
+        index_reg,
+        done_label,
+        access,
+        code_sequence,
+    );
+}
+
+/// Generates code to convert the number in `operand_reg`, of
+/// type `from`, between INT and FLOAT into `result_reg`. Tiny has
+/// no conversion instructions, but every location holds an int and
+/// a float value side by side and arithmetic with a literal updates
+/// both of them alike. The result is therefore built up from zero,
+/// one power of two at a time, keeping each power that does not take
+/// it past the operand. This leaves both values of the result set to
+/// the operand truncated towards zero.
+fn gen_conversion(from: ResultType, operand_reg: Register, result_reg: Register) -> Vec<TinyCode> {
+    // Compares `lhs`, or zero if there is no
+    // `lhs`, with the operand being converted.
+    let compare = |lhs: Option<Register>| match from {
+        ResultType::Int => TinyCode::CmpI(
+            lhs.map_or(OpmrIL::Literal(0), |reg| OpmrIL::Location(Opmr::Reg(reg))),
+            operand_reg,
+        ),
+        ResultType::Float => TinyCode::CmpF(
+            lhs.map_or(OpmrFL::Literal(0.0), |reg| OpmrFL::Location(Opmr::Reg(reg))),
+            operand_reg,
+        ),
+    };
+    let negative_label: Label = three_addr_code_ir::Label::new().into();
+    let done_label: Label = three_addr_code_ir::Label::new().into();
+
+    let mut code_sequence = vec![
+        TinyCode::Move(OpmrL::Int(OpmrIL::Literal(0)), Opmr::Reg(result_reg)),
+        // 0 > operand
+        compare(None),
+        TinyCode::Jgt(negative_label),
+    ];
+
+    // Non-negative operands are approached from below
+    (0..31).rev().for_each(|bit| {
+        let keep_label: Label = three_addr_code_ir::Label::new().into();
+        code_sequence.push(TinyCode::AddI(OpmrIL::Literal(1 << bit), result_reg));
+        code_sequence.push(compare(Some(result_reg)));
+        code_sequence.push(TinyCode::Jle(keep_label));
+        code_sequence.push(TinyCode::SubI(OpmrIL::Literal(1 << bit), result_reg));
+        code_sequence.push(TinyCode::Label(keep_label));
+    });
+    code_sequence.push(TinyCode::Jmp(done_label));
+
+    // and negative operands from above.
+    code_sequence.push(TinyCode::Label(negative_label));
+    (0..31).rev().for_each(|bit| {
+        let keep_label: Label = three_addr_code_ir::Label::new().into();
+        code_sequence.push(TinyCode::SubI(OpmrIL::Literal(1 << bit), result_reg));
+        code_sequence.push(compare(Some(result_reg)));
+        code_sequence.push(TinyCode::Jge(keep_label));
+        code_sequence.push(TinyCode::AddI(OpmrIL::Literal(1 << bit), result_reg));
+        code_sequence.push(TinyCode::Label(keep_label));
+    });
+    code_sequence.push(TinyCode::Label(done_label));
+
+    code_sequence
+}
+
 #[derive(Debug, Default)]
 pub struct TinyCodeSequence {
     pub sequence: Vec<TinyCode>,
@@ -243,18 +438,15 @@ impl From<RegisterAllocatedThreeAddressCode> for TinyCodeSequence {
             }
         });
 
-        let is_branch_or_ret_instr = tac.is_branch() || tac.is_return();
+        let is_ret_instr = tac.is_return();
+        let is_branch_or_ret_instr = tac.is_branch() || is_ret_instr;
 
         // Generate tiny code for this 3AC
         match tac {
-            ThreeAddressCode::AddI {
-                lhs,
-                rhs,
-                temp_result,
-            } => {
+            ThreeAddressCode::AddI { lhs, rhs, result } => {
                 let lhs_reg = register_allocations[&lhs.into()].into();
                 let rhs_reg = register_allocations[&rhs.into()].into();
-                let result_reg = register_allocations[&temp_result.into()].into();
+                let result_reg = register_allocations[&result.into()].into();
 
                 // Move `lhs_reg` to `result_reg`
                 code_sequence.push(TinyCode::Move(
@@ -268,14 +460,10 @@ impl From<RegisterAllocatedThreeAddressCode> for TinyCodeSequence {
                     result_reg,
                 ));
             }
-            ThreeAddressCode::SubI {
-                lhs,
-                rhs,
-                temp_result,
-            } => {
+            ThreeAddressCode::SubI { lhs, rhs, result } => {
                 let lhs_reg = register_allocations[&lhs.into()].into();
                 let rhs_reg = register_allocations[&rhs.into()].into();
-                let result_reg = register_allocations[&temp_result.into()].into();
+                let result_reg = register_allocations[&result.into()].into();
 
                 // Move `lhs_reg` to `result_reg`
                 code_sequence.push(TinyCode::Move(
@@ -289,14 +477,10 @@ impl From<RegisterAllocatedThreeAddressCode> for TinyCodeSequence {
                     result_reg,
                 ));
             }
-            ThreeAddressCode::MulI {
-                lhs,
-                rhs,
-                temp_result,
-            } => {
+            ThreeAddressCode::MulI { lhs, rhs, result } => {
                 let lhs_reg = register_allocations[&lhs.into()].into();
                 let rhs_reg = register_allocations[&rhs.into()].into();
-                let result_reg = register_allocations[&temp_result.into()].into();
+                let result_reg = register_allocations[&result.into()].into();
 
                 // Move `lhs_reg` to `result_reg`
                 code_sequence.push(TinyCode::Move(
@@ -310,14 +494,10 @@ impl From<RegisterAllocatedThreeAddressCode> for TinyCodeSequence {
                     result_reg,
                 ));
             }
-            ThreeAddressCode::DivI {
-                lhs,
-                rhs,
-                temp_result,
-            } => {
+            ThreeAddressCode::DivI { lhs, rhs, result } => {
                 let lhs_reg = register_allocations[&lhs.into()].into();
                 let rhs_reg = register_allocations[&rhs.into()].into();
-                let result_reg = register_allocations[&temp_result.into()].into();
+                let result_reg = register_allocations[&result.into()].into();
 
                 // Move `lhs_reg` to `result_reg`
                 code_sequence.push(TinyCode::Move(
@@ -331,14 +511,39 @@ impl From<RegisterAllocatedThreeAddressCode> for TinyCodeSequence {
                     result_reg,
                 ));
             }
-            ThreeAddressCode::AddF {
-                lhs,
-                rhs,
-                temp_result,
-            } => {
+            ThreeAddressCode::ModI { lhs, rhs, result } => {
                 let lhs_reg = register_allocations[&lhs.into()].into();
                 let rhs_reg = register_allocations[&rhs.into()].into();
-                let result_reg = register_allocations[&temp_result.into()].into();
+                let result_reg = register_allocations[&result.into()].into();
+
+                // Tiny has no remainder instruction, so compute
+                // `lhs - (lhs / rhs) * rhs` in `result_reg`
+                // without clobbering the operand registers.
+                code_sequence.push(TinyCode::Move(
+                    OpmrL::Int(OpmrIL::Location(Opmr::Reg(lhs_reg))),
+                    Opmr::Reg(result_reg),
+                ));
+                code_sequence.push(TinyCode::DivI(
+                    OpmrIL::Location(Opmr::Reg(rhs_reg)),
+                    result_reg,
+                ));
+                code_sequence.push(TinyCode::MulI(
+                    OpmrIL::Location(Opmr::Reg(rhs_reg)),
+                    result_reg,
+                ));
+
+                // `result_reg` now holds `(lhs / rhs) * rhs - lhs`,
+                // which only needs its sign flipped.
+                code_sequence.push(TinyCode::SubI(
+                    OpmrIL::Location(Opmr::Reg(lhs_reg)),
+                    result_reg,
+                ));
+                code_sequence.push(TinyCode::MulI(OpmrIL::Literal(-1), result_reg));
+            }
+            ThreeAddressCode::AddF { lhs, rhs, result } => {
+                let lhs_reg = register_allocations[&lhs.into()].into();
+                let rhs_reg = register_allocations[&rhs.into()].into();
+                let result_reg = register_allocations[&result.into()].into();
 
                 // Move lhs_reg to result_reg
                 code_sequence.push(TinyCode::Move(
@@ -352,14 +557,10 @@ impl From<RegisterAllocatedThreeAddressCode> for TinyCodeSequence {
                     result_reg,
                 ));
             }
-            ThreeAddressCode::SubF {
-                lhs,
-                rhs,
-                temp_result,
-            } => {
+            ThreeAddressCode::SubF { lhs, rhs, result } => {
                 let lhs_reg = register_allocations[&lhs.into()].into();
                 let rhs_reg = register_allocations[&rhs.into()].into();
-                let result_reg = register_allocations[&temp_result.into()].into();
+                let result_reg = register_allocations[&result.into()].into();
 
                 // Move lhs_reg to result_reg
                 code_sequence.push(TinyCode::Move(
@@ -373,14 +574,10 @@ impl From<RegisterAllocatedThreeAddressCode> for TinyCodeSequence {
                     result_reg,
                 ));
             }
-            ThreeAddressCode::MulF {
-                lhs,
-                rhs,
-                temp_result,
-            } => {
+            ThreeAddressCode::MulF { lhs, rhs, result } => {
                 let lhs_reg = register_allocations[&lhs.into()].into();
                 let rhs_reg = register_allocations[&rhs.into()].into();
-                let result_reg = register_allocations[&temp_result.into()].into();
+                let result_reg = register_allocations[&result.into()].into();
 
                 // Move lhs_reg to result_reg
                 code_sequence.push(TinyCode::Move(
@@ -394,14 +591,10 @@ impl From<RegisterAllocatedThreeAddressCode> for TinyCodeSequence {
                     result_reg,
                 ));
             }
-            ThreeAddressCode::DivF {
-                lhs,
-                rhs,
-                temp_result,
-            } => {
+            ThreeAddressCode::DivF { lhs, rhs, result } => {
                 let lhs_reg = register_allocations[&lhs.into()].into();
                 let rhs_reg = register_allocations[&rhs.into()].into();
-                let result_reg = register_allocations[&temp_result.into()].into();
+                let result_reg = register_allocations[&result.into()].into();
 
                 // Move lhs_reg to result_reg
                 code_sequence.push(TinyCode::Move(
@@ -442,11 +635,37 @@ impl From<RegisterAllocatedThreeAddressCode> for TinyCodeSequence {
                 // Generate tiny code for the 3AC
                 code_sequence.push(TinyCode::ReadI(Opmr::Reg(ident_reg)));
             }
-            ThreeAddressCode::WriteI { identifier } => {
-                let ident_reg = register_allocations[&identifier.into()].into();
+            ThreeAddressCode::WriteI { value } => {
+                let value_reg = register_allocations[&value.into()].into();
 
                 // Generate tiny code for the 3AC
-                code_sequence.push(TinyCode::WriteI(Opmr::Reg(ident_reg)));
+                code_sequence.push(TinyCode::WriteI(Opmr::Reg(value_reg)));
+            }
+            ThreeAddressCode::LoadElementI {
+                array,
+                index,
+                temp_result,
+            } => {
+                let index_reg = register_allocations[&index.into()].into();
+                let result_reg = register_allocations[&temp_result.into()].into();
+
+                // Generate tiny code for the 3AC
+                code_sequence.extend(gen_element_access(array, index_reg, |element| {
+                    TinyCode::Move(OpmrL::Int(OpmrIL::Location(element)), Opmr::Reg(result_reg))
+                }));
+            }
+            ThreeAddressCode::StoreElementI {
+                array,
+                index,
+                value,
+            } => {
+                let index_reg = register_allocations[&index.into()].into();
+                let value_reg = register_allocations[&value.into()].into();
+
+                // Generate tiny code for the 3AC
+                code_sequence.extend(gen_element_access(array, index_reg, |element| {
+                    TinyCode::Move(OpmrL::Int(OpmrIL::Location(Opmr::Reg(value_reg))), element)
+                }));
             }
             ThreeAddressCode::StoreF { lhs, rhs } => {
                 let lhs_reg = register_allocations[&lhs.into()].into();
@@ -475,11 +694,63 @@ impl From<RegisterAllocatedThreeAddressCode> for TinyCodeSequence {
                 // Generate tiny code for the 3AC
                 code_sequence.push(TinyCode::ReadF(Opmr::Reg(ident_reg)));
             }
-            ThreeAddressCode::WriteF { identifier } => {
-                let ident_reg = register_allocations[&identifier.into()].into();
+            ThreeAddressCode::WriteF { value } => {
+                let value_reg = register_allocations[&value.into()].into();
+
+                // Generate tiny code for the 3AC
+                code_sequence.push(TinyCode::WriteF(Opmr::Reg(value_reg)));
+            }
+            ThreeAddressCode::LoadElementF {
+                array,
+                index,
+                temp_result,
+            } => {
+                let index_reg = register_allocations[&index.into()].into();
+                let result_reg = register_allocations[&temp_result.into()].into();
 
                 // Generate tiny code for the 3AC
-                code_sequence.push(TinyCode::WriteF(Opmr::Reg(ident_reg)));
+                code_sequence.extend(gen_element_access(array, index_reg, |element| {
+                    TinyCode::Move(
+                        OpmrL::Float(OpmrFL::Location(element)),
+                        Opmr::Reg(result_reg),
+                    )
+                }));
+            }
+            ThreeAddressCode::StoreElementF {
+                array,
+                index,
+                value,
+            } => {
+                let index_reg = register_allocations[&index.into()].into();
+                let value_reg = register_allocations[&value.into()].into();
+
+                // Generate tiny code for the 3AC
+                code_sequence.extend(gen_element_access(array, index_reg, |element| {
+                    TinyCode::Move(
+                        OpmrL::Float(OpmrFL::Location(Opmr::Reg(value_reg))),
+                        element,
+                    )
+                }));
+            }
+            ThreeAddressCode::IntToFloat {
+                operand,
+                temp_result,
+            } => {
+                let operand_reg = register_allocations[&operand.into()].into();
+                let result_reg = register_allocations[&temp_result.into()].into();
+
+                // Generate tiny code for the 3AC
+                code_sequence.extend(gen_conversion(ResultType::Int, operand_reg, result_reg));
+            }
+            ThreeAddressCode::FloatToInt {
+                operand,
+                temp_result,
+            } => {
+                let operand_reg = register_allocations[&operand.into()].into();
+                let result_reg = register_allocations[&temp_result.into()].into();
+
+                // Generate tiny code for the 3AC
+                code_sequence.extend(gen_conversion(ResultType::Float, operand_reg, result_reg));
             }
             ThreeAddressCode::WriteS { identifier } => {
                 code_sequence.push(TinyCode::WriteS(identifier.0));
@@ -691,11 +962,16 @@ impl From<RegisterAllocatedThreeAddressCode> for TinyCodeSequence {
         // going to be a branch, add the end_of_bb spills and add back the
         // previously popped tiny branch instruction.
         if let Some(end_of_bb_spills) = end_of_bb_spills {
-            let instr = if is_branch_or_ret_instr {
-                code_sequence.pop()
+            // A return is lowered to `unlnk` followed by `ret` and the
+            // spills must happen before the frame is unlinked.
+            let num_trailing_instrs = if is_ret_instr {
+                2
+            } else if is_branch_or_ret_instr {
+                1
             } else {
-                None
+                0
             };
+            let instrs = code_sequence.split_off(code_sequence.len() - num_trailing_instrs);
 
             // Generate end of bb spills
             end_of_bb_spills.into_iter().for_each(|spill| {
@@ -718,9 +994,7 @@ impl From<RegisterAllocatedThreeAddressCode> for TinyCodeSequence {
                 }
             });
 
-            if let Some(instr) = instr {
-                code_sequence.push(instr);
-            }
+            code_sequence.extend(instrs);
         }
 
         TinyCodeSequence {
@@ -740,22 +1014,68 @@ impl From<(Vec<RegisterAllocatedThreeAddressCode>, usize, bool, bool)> for TinyC
     ) -> Self {
         // Add all symbol declarations to tiny code sequence
         let mut result = if first {
-            let symbol_decls = SymbolTable::global_symbols()
+            let mut symbol_decls: Vec<TinyCode> = SymbolTable::global_symbols()
                 .into_iter()
-                .map(|symbol| match &*symbol {
-                    NonFunctionScopedSymbol::String { name, value } => TinyCode::Str(Sid {
+                .flat_map(|symbol| match &*symbol {
+                    NonFunctionScopedSymbol::String { name, value } => vec![TinyCode::Str(Sid {
                         id: name.clone(),
                         value: value.clone(),
-                    }),
-                    NonFunctionScopedSymbol::Int { name } => TinyCode::Var(name.clone()),
-                    NonFunctionScopedSymbol::Float { name } => TinyCode::Var(name.clone()),
+                    })],
+                    NonFunctionScopedSymbol::Int { name } => vec![TinyCode::Var(name.clone())],
+                    NonFunctionScopedSymbol::Float { name } => vec![TinyCode::Var(name.clone())],
+                    NonFunctionScopedSymbol::Bool { name } => vec![TinyCode::Var(name.clone())],
+                    // Constants are substituted for their uses
+                    NonFunctionScopedSymbol::Const { .. } => vec![],
+                    NonFunctionScopedSymbol::Record { .. } => symbol
+                        .field_symbols()
+                        .iter()
+                        .map(|field| TinyCode::Var(field.name().to_owned()))
+                        .collect(),
+                    NonFunctionScopedSymbol::IntArray { name, size }
+                    | NonFunctionScopedSymbol::FloatArray { name, size } => (0..*size)
+                        .map(|element| TinyCode::Var(global_element_name(name, element)))
+                        .collect(),
                 })
                 .collect();
 
+            symbol_decls.extend(SymbolTable::string_literals().into_iter().map(|symbol| {
+                match &*symbol {
+                    NonFunctionScopedSymbol::String { name, value } => TinyCode::Str(Sid {
+                        id: name.clone(),
+                        value: value.clone(),
+                    }),
+                    _ => unreachable!("String literals are always interned as STRING symbols"),
+                }
+            }));
+
+            if cfg!(feature = "array-bounds-checks") && SymbolTable::has_array_symbols() {
+                symbol_decls.push(TinyCode::Str(Sid {
+                    id: ARRAY_INDEX_ERROR.to_owned(),
+                    value: "\"Array index out of bounds!\\n\"".to_owned(),
+                }));
+            }
+
             let mut result = TinyCodeSequence {
                 sequence: symbol_decls,
             };
 
+            // Initialize global variables before `main` runs.
+            result
+                .sequence
+                .extend(
+                    SymbolTable::global_initializers()
+                        .into_iter()
+                        .map(|(symbol, value)| {
+                            let value = match value {
+                                NumValue::Int(n) => OpmrL::Int(OpmrIL::Literal(n)),
+                                NumValue::Float(n) => OpmrL::Float(OpmrFL::Literal(n)),
+                                NumValue::Bool(b) => OpmrL::Int(OpmrIL::Literal(b as i32)),
+                            };
+
+                            TinyCode::Move(value, Opmr::Id(symbol.into()))
+                        }),
+                );
+
             // Insert empty slot for result of `main` and then a
             // call to `main` itself.
             let main_func_symbol =
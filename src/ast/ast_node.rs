@@ -1,75 +1,179 @@
+use crate::span::Span;
 use crate::symbol_table::symbol::data;
-use crate::symbol_table::symbol::data::FunctionScopedSymbol;
 use crate::symbol_table::symbol::function;
 use crate::symbol_table::symbol::NumType;
 use std::rc::Rc;
 
 /// Differentiates an addition `Add` node
 /// from a subtraction `Add` node.
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, derive_more::Display)]
 pub enum AddOp {
+    #[display(fmt = "+")]
     Add,
+    #[display(fmt = "-")]
     Sub,
 }
 
 /// Differentiates an multiplication
-/// `Mul` node from a division
-/// `Mul` node.
-#[derive(Debug, Copy, Clone)]
+/// `Mul` node from a division or an
+/// integer remainder `Mul` node.
+#[derive(Debug, Copy, Clone, derive_more::Display)]
 pub enum MulOp {
+    #[display(fmt = "*")]
     Mul,
+    #[display(fmt = "/")]
+    Div,
+    #[display(fmt = "%")]
+    Mod,
+}
+
+/// The arithmetic operation that a
+/// compound assignment, such as `+=`,
+/// applies to its target. `%` has no
+/// compound assignment form.
+#[derive(Debug, Copy, Clone, derive_more::Display)]
+pub enum CompoundOp {
+    #[display(fmt = "+=")]
+    Add,
+    #[display(fmt = "-=")]
+    Sub,
+    #[display(fmt = "*=")]
+    Mul,
+    #[display(fmt = "/=")]
     Div,
 }
 
 /// Represents the comparison
 /// operation in a boolean expression.
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, derive_more::Display)]
 pub enum CmpOp {
     /// Less than
+    #[display(fmt = "<")]
     Lt,
     /// Greater than
+    #[display(fmt = ">")]
     Gt,
     /// Equal to
+    #[display(fmt = "=")]
     Eq,
     /// Not equal to
+    #[display(fmt = "!=")]
     Ne,
     /// Less than or equal to
+    #[display(fmt = "<=")]
     Lte,
     /// Greater than or equal to
+    #[display(fmt = ">=")]
     Gte,
 }
 
+impl CmpOp {
+    /// Returns whether the operator only tests
+    /// for (in)equality, which is the only kind
+    /// of comparison defined for BOOL values.
+    pub fn is_equality(self) -> bool {
+        matches!(self, CmpOp::Eq | CmpOp::Ne)
+    }
+
+    /// Returns the comparison operator that
+    /// evaluates to true exactly when `self`
+    /// evaluates to false.
+    pub fn negated(self) -> Self {
+        match self {
+            CmpOp::Lt => CmpOp::Gte,
+            CmpOp::Gt => CmpOp::Lte,
+            CmpOp::Eq => CmpOp::Ne,
+            CmpOp::Ne => CmpOp::Eq,
+            CmpOp::Lte => CmpOp::Gt,
+            CmpOp::Gte => CmpOp::Lt,
+        }
+    }
+}
+
 /// Represents an identifier
 /// for a declared data symbol.
 #[derive(Debug, Clone)]
 pub struct Identifier {
     pub symbol: data::Symbol,
+    pub span: Span,
 }
 
 impl Identifier {
     pub fn data_type(&self) -> data::DataType {
         match &self.symbol {
-            data::Symbol::NonFunctionScopedSymbol(symbol) => match **symbol {
-                data::NonFunctionScopedSymbol::String { .. } => data::DataType::String,
-                data::NonFunctionScopedSymbol::Int { .. } => data::DataType::Num(NumType::Int),
-                data::NonFunctionScopedSymbol::Float { .. } => data::DataType::Num(NumType::Float),
-            },
-            data::Symbol::FunctionScopedSymbol(symbol) => match **symbol {
-                data::FunctionScopedSymbol::Int { .. } => data::DataType::Num(NumType::Int),
-                data::FunctionScopedSymbol::Float { .. } => data::DataType::Num(NumType::Float),
-            },
+            data::Symbol::NonFunctionScopedSymbol(symbol) => symbol.data_type(),
+            data::Symbol::FunctionScopedSymbol(symbol) => symbol.data_type(),
+        }
+    }
+
+    /// Returns the type of the value of the identifier,
+    /// or of its elements if it is an array.
+    pub fn ty(&self) -> Type {
+        match self.data_type() {
+            data::DataType::Num(num_type) | data::DataType::Array(num_type) => Type::Num(num_type),
+            data::DataType::String => Type::String,
+            data::DataType::Record => Type::Record,
+        }
+    }
+}
+
+/// The type of the value an expression evaluates to,
+/// as resolved by the type checker.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, derive_more::Display)]
+pub enum Type {
+    #[display(fmt = "{}", _0)]
+    Num(NumType),
+    /// A STRING identifier, which can only be written out.
+    #[display(fmt = "STRING")]
+    String,
+    /// A RECORD identifier, which can only be
+    /// passed to a function as a whole.
+    #[display(fmt = "RECORD")]
+    Record,
+    /// The result of a call to a VOID function.
+    #[display(fmt = "VOID")]
+    Void,
+}
+
+impl Type {
+    /// Returns whether the type is INT or FLOAT.
+    pub fn is_numeric(self) -> bool {
+        matches!(self, Type::Num(NumType::Int) | Type::Num(NumType::Float))
+    }
+
+    /// Returns whether a value of this type can be assigned
+    /// to, or passed as, a `num_type` value. INT values are
+    /// converted to FLOAT, but FLOAT values are never
+    /// converted to INT, and BOOL values are never converted
+    /// to or from numbers.
+    pub fn is_assignable_to(self, num_type: NumType) -> bool {
+        match self {
+            Type::Num(NumType::Int) => num_type != NumType::Bool,
+            Type::Num(value_type) => value_type == num_type,
+            Type::String | Type::Record | Type::Void => false,
         }
     }
 }
 
 /// Math expressions in Microc
 /// that evaluate to a numeric
-/// value.
+/// or a boolean value.
+#[derive(Debug, Clone)]
+pub struct Expr {
+    pub kind: ExprKind,
+    pub span: Span,
+    /// The type of the value of the expression, which is
+    /// only known once the expression has been type checked.
+    pub ty: Option<Type>,
+}
+
 #[derive(Debug, Clone)]
-pub enum Expr {
+pub enum ExprKind {
     Id(Identifier),
     IntLiteral(i32),
     FloatLiteral(f64),
+    /// `TRUE` or `FALSE`.
+    BoolLiteral(bool),
     Add {
         op: AddOp,
         lhs: Box<Expr>,
@@ -80,13 +184,122 @@ pub enum Expr {
         lhs: Box<Expr>,
         rhs: Box<Expr>,
     },
+    /// Unary minus.
+    Neg(Box<Expr>),
+    /// Read of an array element.
+    Index {
+        array: Identifier,
+        index: Box<Expr>,
+    },
+    /// Explicit conversion, i.e., `INT(x)` or `FLOAT(x)`.
+    Cast {
+        num_type: NumType,
+        expr: Box<Expr>,
+    },
     Call {
         func_symbol: Rc<function::Symbol>,
         args: Vec<Expr>,
     },
+    /// Comparison or logical operation used as
+    /// a BOOL value rather than to branch on.
+    Condition(Box<Condition>),
+    /// `IF (condition) THEN then_expr ELSE else_expr FI`,
+    /// which evaluates to one of the two values.
+    Conditional {
+        condition: Box<Condition>,
+        then_expr: Box<Expr>,
+        else_expr: Box<Expr>,
+    },
     None,
 }
 
+impl Expr {
+    pub fn new(kind: ExprKind, span: Span) -> Self {
+        Self {
+            kind,
+            span,
+            ty: None,
+        }
+    }
+
+    /// Evaluates the type checked expression at compile time.
+    /// Returns `None` if the expression depends on a value only
+    /// known at run time, or if its evaluation overflows or
+    /// divides by zero.
+    pub fn evaluate_constant(&self) -> Option<data::NumValue> {
+        use data::NumValue::{Bool, Float, Int};
+
+        match &self.kind {
+            ExprKind::IntLiteral(n) => Some(Int(*n)),
+            ExprKind::FloatLiteral(n) => Some(Float(*n)),
+            ExprKind::BoolLiteral(b) => Some(Bool(*b)),
+            ExprKind::Condition(condition) => condition.evaluate_constant().map(Bool),
+            ExprKind::Neg(expr) => match expr.evaluate_constant()? {
+                Int(n) => n.checked_neg().map(Int),
+                Float(n) => Some(Float(-n)),
+                Bool(_) => None,
+            },
+            ExprKind::Cast { num_type, expr } => {
+                Some(expr.evaluate_constant()?.convert_to(*num_type))
+            }
+            ExprKind::Add { op, lhs, rhs } => {
+                match (lhs.evaluate_constant()?, rhs.evaluate_constant()?) {
+                    (Int(lhs), Int(rhs)) => match op {
+                        AddOp::Add => lhs.checked_add(rhs),
+                        AddOp::Sub => lhs.checked_sub(rhs),
+                    }
+                    .map(Int),
+                    // Mixed operands are promoted to FLOAT
+                    (lhs, rhs) => {
+                        let (lhs, rhs) = (lhs.as_float(), rhs.as_float());
+                        Some(Float(match op {
+                            AddOp::Add => lhs + rhs,
+                            AddOp::Sub => lhs - rhs,
+                        }))
+                    }
+                }
+            }
+            ExprKind::Mul { op, lhs, rhs } => {
+                match (lhs.evaluate_constant()?, rhs.evaluate_constant()?) {
+                    (Int(lhs), Int(rhs)) => match op {
+                        MulOp::Mul => lhs.checked_mul(rhs),
+                        MulOp::Div => lhs.checked_div(rhs),
+                        MulOp::Mod => lhs.checked_rem(rhs),
+                    }
+                    .map(Int),
+                    (lhs, rhs) => {
+                        let (lhs, rhs) = (lhs.as_float(), rhs.as_float());
+                        match op {
+                            MulOp::Mul => Some(Float(lhs * rhs)),
+                            MulOp::Div if rhs != 0.0 => Some(Float(lhs / rhs)),
+                            // The remainder is only defined for ints
+                            MulOp::Div | MulOp::Mod => None,
+                        }
+                    }
+                }
+            }
+            ExprKind::Conditional {
+                condition,
+                then_expr,
+                else_expr,
+            } => {
+                let value = if condition.evaluate_constant()? {
+                    then_expr
+                } else {
+                    else_expr
+                };
+                match self.ty? {
+                    Type::Num(num_type) => Some(value.evaluate_constant()?.convert_to(num_type)),
+                    _ => None,
+                }
+            }
+            ExprKind::Id(_) | ExprKind::Index { .. } | ExprKind::Call { .. } | ExprKind::None => {
+                None
+            }
+        }
+    }
+}
+
 /// An assignment, which exists only
 /// for building different statements
 /// made up of assign semantics, such as,
@@ -94,23 +307,193 @@ pub enum Expr {
 #[derive(Debug, Clone)]
 pub struct Assignment {
     pub lhs: Identifier,
+    /// Index of the element being assigned
+    /// to, if `lhs` is an array.
+    pub index: Option<Box<Expr>>,
+    /// Operation combining the current value
+    /// of the target with `rhs`, if this is
+    /// a compound assignment.
+    pub op: Option<CompoundOp>,
     pub rhs: Expr,
+    pub span: Span,
 }
 
 /// A boolean expression that evaluates
-/// to either true or false.
+/// to either true or false. Compound
+/// conditions are evaluated with short-circuit
+/// semantics.
 #[derive(Debug, Clone)]
 pub struct Condition {
-    pub cmp_op: CmpOp,
-    pub lhs: Expr,
-    pub rhs: Expr,
+    pub kind: ConditionKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub enum ConditionKind {
+    Cmp {
+        cmp_op: CmpOp,
+        lhs: Expr,
+        rhs: Expr,
+    },
+    And(Box<Condition>, Box<Condition>),
+    Or(Box<Condition>, Box<Condition>),
+    Not(Box<Condition>),
+    /// A BOOL value tested as a condition. While
+    /// parsing, this also wraps any expression that
+    /// has been parsed as part of a condition.
+    Value(Expr),
+}
+
+impl Condition {
+    pub fn new(kind: ConditionKind, span: Span) -> Self {
+        Self { kind, span }
+    }
+
+    /// Returns the condition as an expression, which
+    /// is a BOOL value unless the condition is merely
+    /// a wrapped expression.
+    pub fn into_expr(self) -> Expr {
+        let span = self.span;
+        match self.kind {
+            ConditionKind::Value(expr) => expr,
+            kind => Expr::new(
+                ExprKind::Condition(Box::new(Condition::new(kind, span))),
+                span,
+            ),
+        }
+    }
+
+    /// Evaluates the condition at compile time. Returns
+    /// `None` if the condition depends on a value only
+    /// known at run time.
+    pub fn evaluate_constant(&self) -> Option<bool> {
+        use data::NumValue::{Bool, Int};
+
+        match &self.kind {
+            ConditionKind::Cmp { cmp_op, lhs, rhs } => {
+                let ordering = match (lhs.evaluate_constant()?, rhs.evaluate_constant()?) {
+                    (Int(lhs), Int(rhs)) => lhs.cmp(&rhs),
+                    (Bool(lhs), Bool(rhs)) => lhs.cmp(&rhs),
+                    (lhs, rhs) => lhs.as_float().partial_cmp(&rhs.as_float())?,
+                };
+
+                Some(match cmp_op {
+                    CmpOp::Lt => ordering.is_lt(),
+                    CmpOp::Gt => ordering.is_gt(),
+                    CmpOp::Eq => ordering.is_eq(),
+                    CmpOp::Ne => ordering.is_ne(),
+                    CmpOp::Lte => ordering.is_le(),
+                    CmpOp::Gte => ordering.is_ge(),
+                })
+            }
+            ConditionKind::And(lhs, rhs) => {
+                Some(lhs.evaluate_constant()? && rhs.evaluate_constant()?)
+            }
+            ConditionKind::Or(lhs, rhs) => {
+                Some(lhs.evaluate_constant()? || rhs.evaluate_constant()?)
+            }
+            ConditionKind::Not(condition) => Some(!condition.evaluate_constant()?),
+            ConditionKind::Value(expr) => match expr.evaluate_constant()? {
+                Bool(b) => Some(b),
+                _ => None,
+            },
+        }
+    }
 }
 
 /// Statements in Microc.
 #[derive(Debug, Clone)]
-pub enum Stmt {
+pub struct Stmt {
+    pub kind: StmtKind,
+    pub span: Span,
+}
+
+impl Stmt {
+    pub fn new(kind: StmtKind, span: Span) -> Self {
+        Self { kind, span }
+    }
+
+    /// Whether control can reach the end of the statement,
+    /// i.e., it does not always end in a RETURN, BREAK or
+    /// CONTINUE.
+    pub fn can_complete(&self) -> bool {
+        match &self.kind {
+            StmtKind::If {
+                then_block,
+                else_block,
+                ..
+            } => Stmt::block_can_complete(then_block) || Stmt::block_can_complete(else_block),
+            // A CASE without a default arm has an empty
+            // default block, which runs when no arm matches.
+            StmtKind::Case {
+                arms,
+                default_block,
+                ..
+            } => {
+                arms.iter()
+                    .any(|(_, block)| Stmt::block_can_complete(block))
+                    || Stmt::block_can_complete(default_block)
+            }
+            // The condition of a FOR loop may be false from the start,
+            // while a DO-WHILE loop is only left through its condition
+            // or a BREAK.
+            StmtKind::For { .. } => true,
+            StmtKind::DoWhile { body, .. } => {
+                Stmt::block_can_complete(body) || body.iter().any(Stmt::jumps_out_of_body)
+            }
+            StmtKind::Return(_) | StmtKind::Break | StmtKind::Continue => false,
+            _ => true,
+        }
+    }
+
+    /// Whether control can reach the end of a block.
+    pub fn block_can_complete(block: &[Stmt]) -> bool {
+        block.iter().all(Stmt::can_complete)
+    }
+
+    /// Whether the statement has a BREAK or CONTINUE
+    /// of the loop whose body it is in.
+    fn jumps_out_of_body(&self) -> bool {
+        match &self.kind {
+            StmtKind::Break | StmtKind::Continue => true,
+            StmtKind::If {
+                then_block,
+                else_block,
+                ..
+            } => then_block
+                .iter()
+                .chain(else_block)
+                .any(Stmt::jumps_out_of_body),
+            StmtKind::Case {
+                arms,
+                default_block,
+                ..
+            } => arms
+                .iter()
+                .flat_map(|(_, block)| block)
+                .chain(default_block)
+                .any(Stmt::jumps_out_of_body),
+            // Jumps in a nested loop only leave that loop
+            _ => false,
+        }
+    }
+}
+
+impl From<Assignment> for Stmt {
+    fn from(assignment: Assignment) -> Self {
+        let span = assignment.span;
+        Stmt::new(StmtKind::Assign(assignment), span)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum StmtKind {
     Read(Vec<Identifier>),
-    Write(Vec<Identifier>),
+    /// Writes out each value in order. A value is
+    /// either a numeric expression or a STRING
+    /// identifier, which string literals are
+    /// interned into.
+    Write(Vec<Expr>),
     Assign(Assignment),
     If {
         condition: Condition,
@@ -119,15 +502,45 @@ pub enum Stmt {
     },
     For {
         init: Option<Assignment>,
-        condition: Condition,
+        condition: Box<Condition>,
         incr: Option<Assignment>,
         body: Vec<Stmt>,
     },
+    /// Post-tested loop - the body always
+    /// executes at least once.
+    DoWhile {
+        body: Vec<Stmt>,
+        condition: Condition,
+    },
+    /// Multi-way branch on an INT selector - runs the
+    /// block of the arm whose value matches the selector,
+    /// or the default block if none does. Control never
+    /// falls through from one arm to the next.
+    Case {
+        selector: Expr,
+        arms: Vec<(i32, Vec<Stmt>)>,
+        default_block: Vec<Stmt>,
+    },
+    /// Exits the innermost enclosing loop.
+    Break,
+    /// Skips to the next iteration of the
+    /// innermost enclosing loop.
+    Continue,
+    /// Call statement - the result of the
+    /// called function, if any, is discarded.
+    Call {
+        func_symbol: Rc<function::Symbol>,
+        args: Vec<Expr>,
+    },
     // The return statement has been modeled to reflect
     // the reality of Micro more closely. The return statement
     // in Micro is an assignment to a memory location that is
     // created by the caller, on the stack.
-    Return(Assignment),
+    /// Return statement - the assignment stores
+    /// the returned value into the function's
+    /// return value slot and is absent for a bare
+    /// `RETURN;` in a VOID function.
+    Return(Option<Assignment>),
     None,
 }
 
@@ -142,6 +555,7 @@ pub enum Item {
     Function {
         symbol: Rc<function::Symbol>,
         body: Vec<Stmt>,
+        span: Span,
     },
 }
 
@@ -150,10 +564,20 @@ pub enum Item {
 #[derive(Debug)]
 pub enum AstNode {
     Item(Item),
-    Stmt(Stmt),
+    Stmt(Box<Stmt>),
     Expr(Expr),
 }
 
+impl AstNode {
+    pub fn span(&self) -> Span {
+        match self {
+            AstNode::Item(Item::Function { span, .. }) => *span,
+            AstNode::Stmt(stmt) => stmt.span,
+            AstNode::Expr(expr) => expr.span,
+        }
+    }
+}
+
 pub mod visit {
     use super::*;
 
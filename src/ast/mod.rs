@@ -1 +1,2 @@
 pub mod ast_node;
+pub mod type_check;
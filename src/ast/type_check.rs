@@ -0,0 +1,773 @@
+//! Type checking of the AST. A program is type checked once
+//! it has been parsed, and before it is lowered to 3AC, which
+//! relies on every value having the type its use requires.
+use crate::ast::ast_node::{
+    Assignment, AstNode, Condition, ConditionKind, Expr, ExprKind, Item, MulOp, Stmt, StmtKind,
+    Type,
+};
+use crate::diagnostic::{Code, Diagnostic};
+use crate::error::CompileError;
+use crate::span::Span;
+use crate::symbol_table::symbol::{data, function, NumType};
+use std::fmt::Display;
+
+const INT: Type = Type::Num(NumType::Int);
+const FLOAT: Type = Type::Num(NumType::Float);
+const BOOL: Type = Type::Num(NumType::Bool);
+
+/// Checks the types of the values in `program`, annotating
+/// each expression with the type of its value. Returns every
+/// type error found, in the order the functions of `program`
+/// are in.
+pub fn check_program(program: &mut [AstNode]) -> Vec<CompileError> {
+    let mut type_checker = TypeChecker::default();
+    for ast_node in program {
+        type_checker.check_ast(ast_node);
+    }
+
+    type_checker.errors
+}
+
+/// Checks the types of the values in a constant expression,
+/// which is evaluated as soon as it is parsed. Returns the
+/// first type error found, if any.
+pub fn check_constant(expr: &mut Expr) -> Result<(), CompileError> {
+    let mut type_checker = TypeChecker::default();
+    type_checker.check_expression(expr);
+
+    type_checker.errors.into_iter().next().map_or(Ok(()), Err)
+}
+
+/// Walks the AST, collecting the type errors in it. An
+/// expression with an error in it has no type, and so the
+/// error is not reported again by the expressions using it.
+#[derive(Default)]
+struct TypeChecker {
+    errors: Vec<CompileError>,
+}
+
+impl TypeChecker {
+    fn check_ast(&mut self, ast_node: &mut AstNode) {
+        match ast_node {
+            AstNode::Item(Item::Function { body, .. }) => self.check_block(body),
+            AstNode::Stmt(stmt) => self.check_statement(stmt),
+            AstNode::Expr(expr) => {
+                self.check_expression(expr);
+            }
+        }
+    }
+
+    fn check_block(&mut self, block: &mut [Stmt]) {
+        for stmt in block {
+            self.check_statement(stmt);
+        }
+    }
+
+    fn check_statement(&mut self, stmt: &mut Stmt) {
+        match &mut stmt.kind {
+            StmtKind::Read(identifiers) => {
+                for identifier in identifiers.iter() {
+                    let ty = identifier.ty();
+                    if !ty.is_numeric() {
+                        self.type_error(
+                            Diagnostic::error(
+                                Code::MismatchedTypes,
+                                format!("cannot READ into {} variables", ty),
+                                identifier.span,
+                            )
+                            .with_label("expected an INT or FLOAT variable"),
+                        );
+                    }
+                }
+            }
+            StmtKind::Write(values) => {
+                for value in values.iter_mut() {
+                    match self.check_expression(value) {
+                        Some(Type::Num(_)) | Some(Type::String) | None => (),
+                        Some(ty) => self.type_error(
+                            Diagnostic::error(
+                                Code::MismatchedTypes,
+                                format!("cannot WRITE {} values", ty),
+                                value.span,
+                            )
+                            .with_label("expected a number, BOOL or STRING value"),
+                        ),
+                    }
+                }
+            }
+            StmtKind::Assign(assignment) => self.check_assignment(assignment),
+            StmtKind::If {
+                condition,
+                then_block,
+                else_block,
+            } => {
+                self.check_test(condition);
+                self.check_block(then_block);
+                self.check_block(else_block);
+            }
+            StmtKind::For {
+                init,
+                condition,
+                incr,
+                body,
+            } => {
+                if let Some(init) = init {
+                    self.check_assignment(init);
+                }
+                self.check_test(condition);
+                if let Some(incr) = incr {
+                    self.check_assignment(incr);
+                }
+                self.check_block(body);
+            }
+            StmtKind::DoWhile { body, condition } => {
+                self.check_block(body);
+                self.check_test(condition);
+            }
+            StmtKind::Case {
+                selector,
+                arms,
+                default_block,
+            } => {
+                match self.check_expression(selector) {
+                    Some(INT) | None => (),
+                    Some(ty) => self.type_error(
+                        Diagnostic::error(
+                            Code::MismatchedTypes,
+                            "CASE selector must be an INT value",
+                            selector.span,
+                        )
+                        .with_label(format!("expected INT, found {}", ty)),
+                    ),
+                }
+
+                for (_, block) in arms {
+                    self.check_block(block);
+                }
+                self.check_block(default_block);
+            }
+            StmtKind::Call { func_symbol, args } => self.check_call(func_symbol, args, stmt.span),
+            // The returned value is assigned to the
+            // return value slot of the function.
+            StmtKind::Return(Some(Assignment { lhs, rhs, .. })) => {
+                if let (Type::Num(return_type), Some(ty)) = (lhs.ty(), self.check_expression(rhs)) {
+                    if !ty.is_assignable_to(return_type) {
+                        self.type_error(
+                            Diagnostic::error(
+                                Code::MismatchedTypes,
+                                format!(
+                                    "cannot return {} values from a function returning {}",
+                                    ty, return_type
+                                ),
+                                rhs.span,
+                            )
+                            .with_label(format!("expected {}, found {}", return_type, ty)),
+                        );
+                    }
+                }
+            }
+            StmtKind::Return(None) | StmtKind::Break | StmtKind::Continue | StmtKind::None => (),
+        }
+    }
+
+    /// Checks that the value of `assignment` can be assigned
+    /// to its target, after being combined with the current
+    /// value of the target if it is a compound assignment.
+    fn check_assignment(&mut self, assignment: &mut Assignment) {
+        if let Some(index) = &mut assignment.index {
+            self.check_index(index);
+        }
+        let value = self.check_expression(&mut assignment.rhs);
+
+        let lhs = &assignment.lhs;
+        let num_type = match lhs.ty() {
+            Type::Num(num_type) => num_type,
+            Type::String => {
+                self.errors.push(CompileError::Semantic(
+                    Diagnostic::error(
+                        Code::InvalidAssignment,
+                        "cannot assign to a STRING",
+                        lhs.span,
+                    )
+                    .with_label("STRING constant"),
+                ));
+                return;
+            }
+            Type::Record => {
+                self.errors.push(CompileError::Semantic(
+                    Diagnostic::error(
+                        Code::InvalidAssignment,
+                        "cannot assign to a RECORD",
+                        lhs.span,
+                    )
+                    .with_label("assign to its fields instead"),
+                ));
+                return;
+            }
+            Type::Void => unreachable!("Identifiers always have a value"),
+        };
+
+        if let (Some(op), NumType::Bool) = (assignment.op, num_type) {
+            self.type_error(
+                Diagnostic::error(
+                    Code::MismatchedTypes,
+                    format!("cannot apply `{}` to a BOOL variable", op),
+                    lhs.span,
+                )
+                .with_label("BOOL variable"),
+            );
+            return;
+        }
+
+        match value {
+            Some(ty) if !ty.is_assignable_to(num_type) => self.type_error(
+                Diagnostic::error(
+                    Code::MismatchedTypes,
+                    format!("cannot assign {} values to {} variables", ty, num_type),
+                    assignment.rhs.span,
+                )
+                .with_label(format!("expected {}, found {}", num_type, ty)),
+            ),
+            _ => (),
+        }
+    }
+
+    /// Checks the arguments of a call, spanning `span`, against
+    /// the parameters of the called function. RECORD arguments
+    /// have already been expanded into their fields by the parser.
+    fn check_call(&mut self, func_symbol: &function::Symbol, args: &mut [Expr], span: Span) {
+        let num_params = func_symbol.params().len();
+        let num_slots = func_symbol.param_slots().len();
+        let expected = match num_params {
+            1 => "expected 1 argument".to_owned(),
+            n => format!("expected {} arguments", n),
+        };
+
+        let extra_args = args.get(num_slots..).unwrap_or_default();
+        if let (Some(first), Some(last)) = (extra_args.first(), extra_args.last()) {
+            self.errors.push(CompileError::Semantic(
+                Diagnostic::error(
+                    Code::WrongArgumentCount,
+                    format!("too many arguments to `{}`", func_symbol),
+                    Span::new(first.span.start, last.span.end),
+                )
+                .with_label(expected)
+                .with_secondary(func_symbol.span(), "function declared here"),
+            ));
+        } else if args.len() < num_slots {
+            self.errors.push(CompileError::Semantic(
+                Diagnostic::error(
+                    Code::WrongArgumentCount,
+                    format!("too few arguments to `{}`", func_symbol),
+                    span,
+                )
+                .with_label(expected)
+                .with_secondary(func_symbol.span(), "function declared here"),
+            ));
+        }
+
+        let mut args = args.iter_mut();
+
+        for param in func_symbol.params() {
+            match param {
+                function::ParamType::Num(param_type) => {
+                    let arg = match args.next() {
+                        Some(arg) => arg,
+                        None => return,
+                    };
+
+                    match self.check_expression(arg) {
+                        Some(ty) if !ty.is_assignable_to(*param_type) => self.type_error(
+                            Diagnostic::error(
+                                Code::MismatchedTypes,
+                                format!(
+                                    "cannot pass {} values as {} arguments of `{}`",
+                                    ty, param_type, func_symbol
+                                ),
+                                arg.span,
+                            )
+                            .with_label(format!("expected {}, found {}", param_type, ty))
+                            .with_secondary(func_symbol.span(), "function declared here"),
+                        ),
+                        _ => (),
+                    }
+                }
+                // The value of a VAR parameter is copied back to the
+                // argument, which must be a variable of the same type.
+                function::ParamType::Var(param_type) => {
+                    let arg = match args.next() {
+                        Some(arg) => arg,
+                        None => return,
+                    };
+                    self.check_expression(arg);
+
+                    let is_assignable = match &arg.kind {
+                        ExprKind::Id(identifier) => {
+                            identifier.data_type() == data::DataType::Num(*param_type)
+                        }
+                        ExprKind::Index { array, .. } => {
+                            array.data_type() == data::DataType::Array(*param_type)
+                        }
+                        _ => false,
+                    };
+
+                    if !is_assignable {
+                        self.type_error(
+                            Diagnostic::error(
+                                Code::MismatchedTypes,
+                                format!(
+                                    "only {} variables can be passed as VAR arguments of `{}`",
+                                    param_type, func_symbol
+                                ),
+                                arg.span,
+                            )
+                            .with_label(format!("expected a variable of type {}", param_type))
+                            .with_secondary(func_symbol.span(), "function declared here"),
+                        );
+                    }
+                }
+                function::ParamType::Record(record_type) => {
+                    for field in args.by_ref().take(record_type.fields().len()) {
+                        self.check_expression(field);
+                    }
+                }
+            }
+        }
+
+        // Any other arguments have been reported
+        // above, but may have errors of their own.
+        for arg in args {
+            self.check_expression(arg);
+        }
+    }
+
+    /// Checks the index expression of an array element.
+    fn check_index(&mut self, index: &mut Expr) {
+        match self.check_expression(index) {
+            Some(INT) | None => (),
+            Some(ty) => self.type_error(
+                Diagnostic::error(
+                    Code::MismatchedTypes,
+                    "array index must be an INT value",
+                    index.span,
+                )
+                .with_label(format!("expected INT, found {}", ty)),
+            ),
+        }
+    }
+
+    /// Checks a condition tested by an IF, FOR or
+    /// DO-WHILE statement, or by a conditional value.
+    fn check_test(&mut self, condition: &mut Condition) {
+        match self.check_condition(condition) {
+            Some(BOOL) | None => (),
+            Some(ty) => self.type_error(
+                Diagnostic::error(
+                    Code::MismatchedTypes,
+                    "condition is not a BOOL value",
+                    condition.span,
+                )
+                .with_label(format!("expected BOOL, found {}", ty)),
+            ),
+        }
+    }
+
+    /// Returns the type of the value of `condition`, which is
+    /// BOOL unless the condition is merely a wrapped expression.
+    fn check_condition(&mut self, condition: &mut Condition) -> Option<Type> {
+        let span = condition.span;
+
+        match &mut condition.kind {
+            // Numbers can be compared with each other, and
+            // BOOL values can only be tested for (in)equality.
+            ConditionKind::Cmp { cmp_op, lhs, rhs } => {
+                let lhs = self.check_expression(lhs);
+                let rhs = self.check_expression(rhs);
+
+                if let (Some(lhs), Some(rhs)) = (lhs, rhs) {
+                    let is_comparable = (lhs.is_numeric() && rhs.is_numeric())
+                        || (lhs == BOOL && rhs == BOOL && cmp_op.is_equality());
+
+                    if !is_comparable {
+                        let label = match (lhs, rhs) {
+                            (Type::Num(_), Type::Num(_)) => {
+                                "BOOL values can only be compared with `=` and `!=`"
+                            }
+                            _ => "only numbers and BOOL values can be compared",
+                        };
+
+                        self.type_error(
+                            Diagnostic::error(
+                                Code::MismatchedTypes,
+                                format!(
+                                    "cannot compare {} and {} values with `{}`",
+                                    lhs, rhs, cmp_op
+                                ),
+                                span,
+                            )
+                            .with_label(label),
+                        );
+                    }
+                }
+            }
+            ConditionKind::And(lhs, rhs) => self.check_bool_operands("AND", lhs, rhs),
+            ConditionKind::Or(lhs, rhs) => self.check_bool_operands("OR", lhs, rhs),
+            ConditionKind::Not(operand) => match self.check_condition(operand) {
+                Some(BOOL) | None => (),
+                Some(ty) => self.type_error(
+                    Diagnostic::error(
+                        Code::MismatchedTypes,
+                        "operand of NOT must be a BOOL value",
+                        operand.span,
+                    )
+                    .with_label(format!("expected BOOL, found {}", ty)),
+                ),
+            },
+            ConditionKind::Value(expr) => return self.check_expression(expr),
+        }
+
+        Some(BOOL)
+    }
+
+    fn check_bool_operands(&mut self, op: &str, lhs: &mut Condition, rhs: &mut Condition) {
+        let operands = [
+            (self.check_condition(lhs), lhs.span),
+            (self.check_condition(rhs), rhs.span),
+        ];
+
+        let operand = operands
+            .iter()
+            .find_map(|&(ty, span)| ty.filter(|&ty| ty != BOOL).map(|ty| (ty, span)));
+        if let Some((ty, span)) = operand {
+            self.type_error(
+                Diagnostic::error(
+                    Code::MismatchedTypes,
+                    format!("operands of {} must be BOOL values", op),
+                    span,
+                )
+                .with_label(format!("expected BOOL, found {}", ty)),
+            );
+        }
+    }
+
+    /// Annotates `expr`, and each expression in it, with the
+    /// type of its value. Returns the type, or `None` if there
+    /// is a type error in the expression.
+    fn check_expression(&mut self, expr: &mut Expr) -> Option<Type> {
+        let span = expr.span;
+
+        let ty = match &mut expr.kind {
+            ExprKind::Id(identifier) => Some(identifier.ty()),
+            ExprKind::IntLiteral(_) => Some(INT),
+            ExprKind::FloatLiteral(_) => Some(FLOAT),
+            ExprKind::BoolLiteral(_) => Some(BOOL),
+            ExprKind::Add { op, lhs, rhs } => self.check_arithmetic(*op, lhs, rhs, false),
+            // The remainder is only defined for INTs.
+            ExprKind::Mul { op, lhs, rhs } => {
+                self.check_arithmetic(*op, lhs, rhs, matches!(op, MulOp::Mod))
+            }
+            ExprKind::Neg(operand) => match self.check_expression(operand) {
+                Some(ty) if ty.is_numeric() => Some(ty),
+                Some(ty) => {
+                    self.type_error(
+                        Diagnostic::error(
+                            Code::MismatchedTypes,
+                            format!("cannot negate {} values", ty),
+                            operand.span,
+                        )
+                        .with_label(format!("{} value", ty)),
+                    );
+                    None
+                }
+                None => None,
+            },
+            ExprKind::Index { array, index } => {
+                self.check_index(index);
+                Some(array.ty())
+            }
+            // BOOL values are never converted to or from numbers.
+            ExprKind::Cast {
+                num_type,
+                expr: operand,
+            } => {
+                match self.check_expression(operand) {
+                    Some(ty) if ty.is_numeric() && *num_type != NumType::Bool => (),
+                    Some(ty) => {
+                        let label = if ty == BOOL || *num_type == NumType::Bool {
+                            "BOOL values are never converted to or from numbers"
+                        } else {
+                            "only INT and FLOAT values can be cast"
+                        };
+
+                        self.type_error(
+                            Diagnostic::error(
+                                Code::MismatchedTypes,
+                                format!("cannot cast {} values to {}", ty, num_type),
+                                span,
+                            )
+                            .with_label(label),
+                        );
+                    }
+                    None => (),
+                }
+
+                Some(Type::Num(*num_type))
+            }
+            ExprKind::Call { func_symbol, args } => {
+                self.check_call(func_symbol, args, span);
+
+                Some(match func_symbol.return_type() {
+                    function::ReturnType::Num(num_type) => Type::Num(num_type),
+                    function::ReturnType::Void => Type::Void,
+                })
+            }
+            ExprKind::Condition(condition) => {
+                self.check_condition(condition);
+                Some(BOOL)
+            }
+            // Both values must be BOOL, or both numbers. An INT
+            // value is converted to FLOAT if the other is a FLOAT.
+            ExprKind::Conditional {
+                condition,
+                then_expr,
+                else_expr,
+            } => {
+                self.check_test(condition);
+                let then_type = self.check_expression(then_expr);
+                let else_type = self.check_expression(else_expr);
+
+                match (then_type?, else_type?) {
+                    (INT, FLOAT) | (FLOAT, INT) => Some(FLOAT),
+                    (then_type @ Type::Num(_), else_type) if then_type == else_type => {
+                        Some(then_type)
+                    }
+                    (then_type, else_type) => {
+                        self.type_error(
+                            Diagnostic::error(
+                                Code::MismatchedTypes,
+                                format!(
+                                    "cannot choose between {} and {} values",
+                                    then_type, else_type
+                                ),
+                                else_expr.span,
+                            )
+                            .with_label(format!("{} value", else_type))
+                            .with_secondary(then_expr.span, format!("{} value", then_type)),
+                        );
+                        None
+                    }
+                }
+            }
+            ExprKind::None => None,
+        };
+
+        expr.ty = ty;
+        ty
+    }
+
+    /// Returns the type of an arithmetic operation, whose
+    /// operands must both be numbers, or INTs if `int_only`.
+    /// Mixed INT and FLOAT operands evaluate to a FLOAT.
+    fn check_arithmetic<T: Display>(
+        &mut self,
+        op: T,
+        lhs: &mut Expr,
+        rhs: &mut Expr,
+        int_only: bool,
+    ) -> Option<Type> {
+        let lhs_type = self.check_expression(lhs);
+        let rhs_type = self.check_expression(rhs);
+        let operands = [(lhs_type?, lhs.span), (rhs_type?, rhs.span)];
+
+        let is_operand = |ty: Type| if int_only { ty == INT } else { ty.is_numeric() };
+        if let Some(&(ty, span)) = operands.iter().find(|&&(ty, _)| !is_operand(ty)) {
+            self.type_error(
+                Diagnostic::error(
+                    Code::MismatchedTypes,
+                    format!("cannot apply `{}` to {} values", op, ty),
+                    span,
+                )
+                .with_label(format!("{} value", ty)),
+            );
+            return None;
+        }
+
+        if operands.iter().any(|&(ty, _)| ty == FLOAT) {
+            Some(FLOAT)
+        } else {
+            Some(INT)
+        }
+    }
+
+    fn type_error(&mut self, diagnostic: Diagnostic) {
+        self.errors.push(CompileError::Type(diagnostic));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ast::ast_node::{Assignment, StmtKind};
+    use serial_test::serial;
+
+    lalrpop_mod!(pub microc);
+
+    fn type_errors(program: &str) -> Vec<Diagnostic> {
+        let mut result = microc::ProgramParser::new().parse(program).unwrap();
+
+        check_program(&mut result)
+            .into_iter()
+            .map(|error| match error {
+                CompileError::Type(diagnostic) => diagnostic,
+                error => panic!("unexpected error: {:?}", error),
+            })
+            .collect()
+    }
+
+    #[test]
+    #[serial]
+    fn float_assigned_to_int_is_rejected() {
+        let program = r"
+            PROGRAM test
+            BEGIN
+                INT a;
+
+                FUNCTION VOID main()
+                BEGIN
+                    a := 3.5;
+                END
+            END
+        ";
+
+        let errors = type_errors(program);
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].code, Code::MismatchedTypes);
+        let start = program.find("3.5").unwrap();
+        assert_eq!(errors[0].primary.span.start, start);
+        assert_eq!(errors[0].primary.span.end, start + 3);
+    }
+
+    #[test]
+    #[serial]
+    fn every_type_error_is_reported() {
+        let program = r"
+            PROGRAM test
+            BEGIN
+                INT a;
+                FLOAT b;
+                BOOL c;
+
+                FUNCTION VOID main()
+                BEGIN
+                    a := b;
+                    c := a + 1;
+                    IF (a)
+                        a := c * 2;
+                    FI
+                    a := a % b;
+                END
+            END
+        ";
+
+        let errors = type_errors(program);
+
+        assert_eq!(errors.len(), 5);
+        assert!(errors
+            .windows(2)
+            .all(|pair| pair[0].primary.span.start < pair[1].primary.span.start));
+    }
+
+    #[test]
+    #[serial]
+    fn call_with_wrong_number_of_arguments_is_rejected() {
+        let program = r"
+            PROGRAM test
+            BEGIN
+                INT a;
+
+                FUNCTION INT g(INT x)
+                BEGIN
+                    RETURN x;
+                END
+
+                FUNCTION VOID swap(VAR INT x, VAR INT y)
+                BEGIN
+                    x := y;
+                END
+
+                FUNCTION VOID main()
+                BEGIN
+                    a := g(1, 2);
+                    a := g();
+                    swap(a);
+                    swap(a, a);
+                END
+            END
+        ";
+
+        let mut result = microc::ProgramParser::new().parse(program).unwrap();
+        let errors = check_program(&mut result);
+
+        let messages: Vec<&str> = errors
+            .iter()
+            .map(|error| match error {
+                CompileError::Semantic(diagnostic)
+                    if diagnostic.code == Code::WrongArgumentCount =>
+                {
+                    diagnostic.message.as_str()
+                }
+                error => panic!("unexpected error: {:?}", error),
+            })
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                "too many arguments to `g`",
+                "too few arguments to `g`",
+                "too few arguments to `swap`",
+            ]
+        );
+        assert_eq!(
+            errors[0].diagnostic().primary.span.start,
+            program.find("2);").unwrap()
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn expressions_are_annotated_with_their_types() {
+        let program = r"
+            PROGRAM test
+            BEGIN
+                INT a;
+                FLOAT b;
+
+                FUNCTION VOID main()
+                BEGIN
+                    b := a * 2 + b;
+                END
+            END
+        ";
+
+        let mut result = microc::ProgramParser::new().parse(program).unwrap();
+        assert!(check_program(&mut result).is_empty());
+
+        let body = match &result[..] {
+            [AstNode::Item(Item::Function { body, .. })] => body,
+            ast => panic!("unexpected AST: {:?}", ast),
+        };
+        let rhs = match &body[..] {
+            [Stmt {
+                kind: StmtKind::Assign(Assignment { rhs, .. }),
+                ..
+            }] => rhs,
+            stmts => panic!("unexpected statements: {:?}", stmts),
+        };
+        assert_eq!(rhs.ty, Some(FLOAT));
+        match &rhs.kind {
+            ExprKind::Add { lhs, .. } => assert_eq!(lhs.ty, Some(INT)),
+            kind => panic!("unexpected expression: {:?}", kind),
+        }
+    }
+}
@@ -89,6 +89,8 @@ pub fn is_bb_terminator(tac: &ThreeAddressCode) -> bool {
         | ThreeAddressCode::LteF { .. }
         | ThreeAddressCode::NeF { .. }
         | ThreeAddressCode::EqF { .. }
+        // Return
+        | ThreeAddressCode::Ret
     )
 }
 
@@ -231,7 +233,7 @@ impl From<CodeObject> for BBFunction {
         for tac in code_object.code_sequence {
             if is_bb_terminator(&tac) {
                 // If the this 3AC is a conditional or unconditional
-                // jump code then the we need to add the jump 3AC to
+                // jump or a return then the we need to add the 3AC to
                 // the current bb, close the current bb and then
                 // start a new bb.
                 // Immediately following statements to the current
@@ -272,16 +274,15 @@ impl From<CodeObject> for BBFunction {
 #[cfg(test)]
 mod test {
     use crate::cfg::basic_block::{BBFunction, BBLabel};
+    use crate::span::Span;
     use crate::symbol_table::symbol::function::ReturnType;
     use crate::symbol_table::symbol::{data, function};
     use crate::three_addr_code_ir;
-    use crate::three_addr_code_ir::three_address_code::visit::{
-        CodeObject, ThreeAddressCodeVisitor,
-    };
+    use crate::three_addr_code_ir::three_address_code::visit::ThreeAddressCodeVisitor;
     use crate::three_addr_code_ir::three_address_code::ThreeAddressCode;
     use crate::three_addr_code_ir::three_address_code::ThreeAddressCode::{
         AddF, DivF, EqI, FunctionLabel, Jump, Label, Link, LteI, MulF, MulI, StoreF, StoreI, SubI,
-        WriteF, WriteI, WriteS,
+        WriteF, WriteI,
     };
     use crate::three_addr_code_ir::{
         reset_label_counter, IdentF, LValueF, LValueI, RValueF, TempF,
@@ -349,6 +350,7 @@ mod test {
             ReturnType::Void,
             vec![],
             vec![],
+            Span::default(),
         )));
         let (t1, t2, t3, t4, t5, t6): (TempI, TempI, TempI, TempI, TempI, TempI) =
             (1.into(), 2.into(), 3.into(), 4.into(), 5.into(), 6.into());
@@ -395,7 +397,7 @@ mod test {
                     MulI {
                         lhs: LValueI::Id(a.clone()),
                         rhs: LValueI::Id(b.clone()),
-                        temp_result: t3,
+                        result: LValueI::Temp(t3),
                     },
                     // STOREI $T3 p
                     StoreI {
@@ -472,7 +474,11 @@ mod test {
                     // LABEL label2
                     Label(tac_label2),
                     // WRITEI i
-                    WriteI { identifier: i },
+                    WriteI {
+                        value: LValueI::Id(i),
+                    },
+                    // RET
+                    ThreeAddressCode::Ret,
                 ],
             )
                 .into(),
@@ -525,6 +531,7 @@ mod test {
                 BB3:
                 LABEL label2
                 WRITEI i
+                RET
 
                 ==== 3AC Labels to BB Labels Mappings ===
                 label2: BB3
@@ -595,6 +602,7 @@ mod test {
             ReturnType::Void,
             vec![],
             vec![],
+            Span::default(),
         )));
         let (t1, t2, t3, t4, t5, t6, t7, t8, t9, t10): (
             TempF,
@@ -724,19 +732,19 @@ mod test {
                     DivF {
                         lhs: LValueF::Id(num.clone()),
                         rhs: LValueF::Id(approx.clone()),
-                        temp_result: t8,
+                        result: LValueF::Temp(t8),
                     },
                     // ADDF approx $T8 $T9
                     AddF {
                         lhs: LValueF::Id(approx.clone()),
                         rhs: LValueF::Temp(t8),
-                        temp_result: t9,
+                        result: LValueF::Temp(t9),
                     },
                     // MULTF $T7 $T9 $T10
                     MulF {
                         lhs: LValueF::Temp(t7),
                         rhs: LValueF::Temp(t9),
-                        temp_result: t10,
+                        result: LValueF::Temp(t10),
                     },
                     // STOREF $T10 newapprox
                     StoreF {
@@ -769,7 +777,7 @@ mod test {
                     SubI {
                         lhs: LValueI::Id(i.clone()),
                         rhs: LValueI::Temp(t5),
-                        temp_result: t6,
+                        result: LValueI::Temp(t6),
                     },
                     // STOREI $T6 i
                     StoreI {
@@ -792,8 +800,10 @@ mod test {
                     Label(tac_label2),
                     // WRITEF approx
                     WriteF {
-                        identifier: approx.clone(),
+                        value: LValueF::Id(approx.clone()),
                     },
+                    // RET
+                    ThreeAddressCode::Ret,
                 ],
             )
                 .into(),
@@ -857,6 +867,7 @@ mod test {
             BB4:
             LABEL label2
             WRITEF approx
+            RET
             BBEnd
 
 
@@ -118,30 +118,15 @@ impl From<ThreeAddressCode> for LivenessDecoratedThreeAddressCode {
         let mut out_set = HashSet::new();
 
         match &tac {
-            ThreeAddressCode::AddI {
-                lhs,
-                rhs,
-                temp_result,
-            }
-            | ThreeAddressCode::SubI {
-                lhs,
-                rhs,
-                temp_result,
-            }
-            | ThreeAddressCode::MulI {
-                lhs,
-                rhs,
-                temp_result,
-            }
-            | ThreeAddressCode::DivI {
-                lhs,
-                rhs,
-                temp_result,
-            } => {
+            ThreeAddressCode::AddI { lhs, rhs, result }
+            | ThreeAddressCode::SubI { lhs, rhs, result }
+            | ThreeAddressCode::MulI { lhs, rhs, result }
+            | ThreeAddressCode::DivI { lhs, rhs, result }
+            | ThreeAddressCode::ModI { lhs, rhs, result } => {
                 gen_set.insert(LValue::LValueI(lhs.clone()));
                 gen_set.insert(LValue::LValueI(rhs.clone()));
 
-                kill_set.insert((*temp_result).into());
+                kill_set.insert(LValue::LValueI(result.clone()));
             }
             ThreeAddressCode::StoreI { lhs, rhs } => {
                 if let RValueI::LValue(lvalue) = rhs {
@@ -153,33 +138,30 @@ impl From<ThreeAddressCode> for LivenessDecoratedThreeAddressCode {
             ThreeAddressCode::ReadI { identifier } => {
                 kill_set.insert(LValue::LValueI(LValueI::Id(identifier.clone())));
             }
-            ThreeAddressCode::WriteI { identifier } => {
-                gen_set.insert(LValue::LValueI(LValueI::Id(identifier.clone())));
-            }
-            ThreeAddressCode::AddF {
-                lhs,
-                rhs,
-                temp_result,
+            ThreeAddressCode::WriteI { value } => {
+                gen_set.insert(LValue::LValueI(value.clone()));
             }
-            | ThreeAddressCode::SubF {
-                lhs,
-                rhs,
-                temp_result,
+            // Array elements always live in memory, so only
+            // the index and the value take part in liveness.
+            ThreeAddressCode::LoadElementI {
+                index, temp_result, ..
+            } => {
+                gen_set.insert(LValue::LValueI(index.clone()));
+
+                kill_set.insert((*temp_result).into());
             }
-            | ThreeAddressCode::MulF {
-                lhs,
-                rhs,
-                temp_result,
+            ThreeAddressCode::StoreElementI { index, value, .. } => {
+                gen_set.insert(LValue::LValueI(index.clone()));
+                gen_set.insert(LValue::LValueI(value.clone()));
             }
-            | ThreeAddressCode::DivF {
-                lhs,
-                rhs,
-                temp_result,
-            } => {
+            ThreeAddressCode::AddF { lhs, rhs, result }
+            | ThreeAddressCode::SubF { lhs, rhs, result }
+            | ThreeAddressCode::MulF { lhs, rhs, result }
+            | ThreeAddressCode::DivF { lhs, rhs, result } => {
                 gen_set.insert(LValue::LValueF(lhs.clone()));
                 gen_set.insert(LValue::LValueF(rhs.clone()));
 
-                kill_set.insert((*temp_result).into());
+                kill_set.insert(LValue::LValueF(result.clone()));
             }
             ThreeAddressCode::StoreF { lhs, rhs } => {
                 if let RValueF::LValue(lvalue) = rhs {
@@ -191,8 +173,35 @@ impl From<ThreeAddressCode> for LivenessDecoratedThreeAddressCode {
             ThreeAddressCode::ReadF { identifier } => {
                 kill_set.insert(LValue::LValueF(LValueF::Id(identifier.clone())));
             }
-            ThreeAddressCode::WriteF { identifier } => {
-                gen_set.insert(LValue::LValueF(LValueF::Id(identifier.clone())));
+            ThreeAddressCode::WriteF { value } => {
+                gen_set.insert(LValue::LValueF(value.clone()));
+            }
+            ThreeAddressCode::LoadElementF {
+                index, temp_result, ..
+            } => {
+                gen_set.insert(LValue::LValueI(index.clone()));
+
+                kill_set.insert((*temp_result).into());
+            }
+            ThreeAddressCode::StoreElementF { index, value, .. } => {
+                gen_set.insert(LValue::LValueI(index.clone()));
+                gen_set.insert(LValue::LValueF(value.clone()));
+            }
+            ThreeAddressCode::IntToFloat {
+                operand,
+                temp_result,
+            } => {
+                gen_set.insert(LValue::LValueI(operand.clone()));
+
+                kill_set.insert((*temp_result).into());
+            }
+            ThreeAddressCode::FloatToInt {
+                operand,
+                temp_result,
+            } => {
+                gen_set.insert(LValue::LValueF(operand.clone()));
+
+                kill_set.insert((*temp_result).into());
             }
             ThreeAddressCode::GtI { lhs, rhs, .. }
             | ThreeAddressCode::LtI { lhs, rhs, .. }
@@ -225,40 +234,14 @@ impl From<ThreeAddressCode> for LivenessDecoratedThreeAddressCode {
                 kill_set.insert(LValue::LValueF(op.clone()));
             }
             ThreeAddressCode::Jsr(_) => {
-                SymbolTable::global_symbols()
-                    .into_iter()
-                    .filter_map(|symbol| match symbol.data_type() {
-                        DataType::Num(NumType::Int) => {
-                            Some(LValue::LValueI(LValueI::Id(IdentI(symbol.into()))))
-                        }
-                        DataType::Num(NumType::Float) => {
-                            Some(LValue::LValueF(LValueF::Id(IdentF(symbol.into()))))
-                        }
-                        _ => None,
-                    })
-                    .for_each(|symbol| {
-                        gen_set.insert(symbol);
-                    });
+                gen_set.extend(global_vars());
             }
             // `Ret` 3AC is an exceptional instruction for which we don't add
             // any gen and use sets, but add the out set, which is always all
             // the globals present in the program because global variables may
             // be used after the function returns.
             ThreeAddressCode::Ret => {
-                SymbolTable::global_symbols()
-                    .into_iter()
-                    .filter_map(|symbol| match symbol.data_type() {
-                        DataType::Num(NumType::Int) => {
-                            Some(LValue::LValueI(LValueI::Id(IdentI(symbol.into()))))
-                        }
-                        DataType::Num(NumType::Float) => {
-                            Some(LValue::LValueF(LValueF::Id(IdentF(symbol.into()))))
-                        }
-                        _ => None,
-                    })
-                    .for_each(|symbol| {
-                        out_set.insert(symbol);
-                    });
+                out_set.extend(global_vars());
             }
             _ => (),
         }
@@ -274,6 +257,30 @@ impl From<ThreeAddressCode> for LivenessDecoratedThreeAddressCode {
     }
 }
 
+/// Returns the global variables that may be held in registers,
+/// i.e., the scalar globals and the fields of global RECORDs.
+fn global_vars() -> Vec<LValue> {
+    SymbolTable::global_symbols()
+        .into_iter()
+        .flat_map(|symbol| match &*symbol {
+            data::NonFunctionScopedSymbol::Record { .. } => {
+                symbol.field_symbols().into_iter().map(Rc::new).collect()
+            }
+            _ => vec![symbol],
+        })
+        .filter_map(|symbol| match symbol.data_type() {
+            // BOOL values are stored as INTs
+            DataType::Num(NumType::Int) | DataType::Num(NumType::Bool) => {
+                Some(LValue::LValueI(LValueI::Id(IdentI(symbol.into()))))
+            }
+            DataType::Num(NumType::Float) => {
+                Some(LValue::LValueF(LValueF::Id(IdentF(symbol.into()))))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
 impl Display for LivenessDecoratedThreeAddressCode {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         // With enough global variables, max_space might lead to an
@@ -466,13 +473,17 @@ impl LivenessDecoratedControlFlowGraph {
                 //
                 // 2. If this is a bb terminator then the current 3AC node may
                 // have multiple successors.
-                let mut out_set = HashSet::new();
-
-                // If the current 3AC is not an unconditional jump then the
-                // successor 3AC node's (which we actually visited in the previous
-                // loop pass) IN set is part of the current 3AC node's OUT
+                //
+                // The OUT set starts off as the node's current OUT set, as
+                // some nodes have variables that are live out of them
+                // regardless of their successors - globals after a `Ret`.
+                let mut out_set = tac.out_set().borrow().clone();
+
+                // If the current 3AC is not an unconditional jump or a return
+                // then the successor 3AC node's (which we actually visited in the
+                // previous loop pass) IN set is part of the current 3AC node's OUT
                 // set.
-                if !tac.tac().is_unconditional_branch() {
+                if !tac.tac().is_unconditional_branch() && !tac.tac().is_return() {
                     out_set.extend(successor_tac_node_in_set);
                 }
 
@@ -495,8 +506,10 @@ impl LivenessDecoratedControlFlowGraph {
                 let out_set_minus_kill_set = &out_set - &*tac.kill_set().borrow();
                 let in_set = &out_set_minus_kill_set | &*tac.gen_set().borrow();
 
-                let out_set_changed = out_set != *tac.out_set().borrow();
-                let in_set_changed = in_set != *tac.in_set().borrow();
+                // The IN and OUT sets only ever grow, so a set
+                // has changed only if it gained new variables.
+                let out_set_changed = !out_set.is_subset(&tac.out_set().borrow());
+                let in_set_changed = !in_set.is_subset(&tac.in_set().borrow());
 
                 if out_set_changed {
                     tac.out_set().borrow_mut().extend(out_set);
@@ -524,6 +537,40 @@ impl LivenessDecoratedControlFlowGraph {
 impl From<ControlFlowGraph> for LivenessDecoratedControlFlowGraph {
     fn from(cfg: ControlFlowGraph) -> Self {
         let (bb_map, bbs) = cfg.into_parts();
+
+        // The caller reads the function's return value, and the
+        // values of its VAR parameters, after the function returns,
+        // which makes their slots live out of every `Ret`.
+        let returned_values: Vec<LValue> = bbs
+            .values()
+            .next()
+            .map(|bb| match bb.seq().first() {
+                Some(ThreeAddressCode::FunctionLabel(func)) => func
+                    .0
+                    .return_value_symbol()
+                    .into_iter()
+                    .chain(func.0.var_param_symbols())
+                    .collect(),
+                _ => vec![],
+            })
+            .unwrap_or_default()
+            .into_iter()
+            .map(|symbol| match symbol {
+                data::FunctionScopedSymbol::Int { .. }
+                | data::FunctionScopedSymbol::Bool { .. } => LValue::LValueI(LValueI::Id(IdentI(
+                    data::Symbol::FunctionScopedSymbol(Rc::new(symbol)),
+                ))),
+                data::FunctionScopedSymbol::Float { .. } => LValue::LValueF(LValueF::Id(IdentF(
+                    data::Symbol::FunctionScopedSymbol(Rc::new(symbol)),
+                ))),
+                data::FunctionScopedSymbol::IntArray { .. }
+                | data::FunctionScopedSymbol::FloatArray { .. }
+                | data::FunctionScopedSymbol::Record { .. } => {
+                    unreachable!("Functions cannot return arrays or records.")
+                }
+            })
+            .collect();
+
         let mut cfg = Self {
             bb_map,
             bbs: bbs
@@ -537,6 +584,15 @@ impl From<ControlFlowGraph> for LivenessDecoratedControlFlowGraph {
                 .collect(),
         };
 
+        cfg.basic_blocks()
+            .flat_map(|(_, bb)| bb.seq())
+            .filter(|tac| tac.tac().is_return())
+            .for_each(|tac| {
+                tac.out_set()
+                    .borrow_mut()
+                    .extend(returned_values.iter().cloned());
+            });
+
         cfg.finalize_in_and_out_sets();
         cfg
     }
@@ -566,6 +622,7 @@ mod test {
         LivenessDecoratedThreeAddressCode, LivenessMetadata,
     };
     use crate::cfg::ControlFlowGraph;
+    use crate::span::Span;
     use crate::symbol_table::symbol::function::ReturnType;
     use crate::symbol_table::symbol::{data, function};
     use crate::symbol_table::{symbol_table_test_setup, SymbolTable};
@@ -573,10 +630,10 @@ mod test {
     use crate::three_addr_code_ir::three_address_code::visit::ThreeAddressCodeVisitor;
     use crate::three_addr_code_ir::three_address_code::ThreeAddressCode;
     use crate::three_addr_code_ir::three_address_code::ThreeAddressCode::{
-        FunctionLabel, Jump, Label, Link, LteI, MulI, StoreI, WriteI,
+        FunctionLabel, Jump, Label, Link, LteI, MulI, Ret, StoreI, WriteI,
     };
     use crate::three_addr_code_ir::{
-        reset_label_counter, FunctionIdent, IdentI, LValueI, RValueI, TempI,
+        reset_label_counter, FunctionIdent, IdentA, IdentI, LValueI, RValueI, TempI,
     };
     use linked_hash_map::LinkedHashMap;
     use serial_test::serial;
@@ -666,7 +723,7 @@ mod test {
         let bb_label: BBLabel = 0.into();
 
         let seq = vec![ThreeAddressCode::WriteI {
-            identifier: a.clone(),
+            value: LValueI::Id(a.clone()),
         }];
 
         let immutable_bb: ImmutableBasicBlock = (bb_label, seq).into();
@@ -676,7 +733,7 @@ mod test {
             label: immutable_bb.label(),
             seq: vec![LivenessDecoratedThreeAddressCode {
                 tac: ThreeAddressCode::WriteI {
-                    identifier: a.clone(),
+                    value: LValueI::Id(a.clone()),
                 },
                 liveness_metadata: LivenessMetadata::builder()
                     .gen_set({
@@ -733,6 +790,67 @@ mod test {
         assert_eq!(expected_liveness_decorated_bb, actual_liveness_decorated_bb);
     }
 
+    #[test]
+    fn element_instructions_gen_index_and_value_but_never_the_array() {
+        let a = IdentA(data::Symbol::NonFunctionScopedSymbol(Rc::new(
+            data::NonFunctionScopedSymbol::IntArray {
+                name: "A".to_owned(),
+                size: 4,
+            },
+        )));
+        let (t1, t2, t3): (TempI, TempI, TempI) = (1.into(), 2.into(), 3.into());
+
+        let bb_label: BBLabel = 0.into();
+
+        let seq = vec![
+            ThreeAddressCode::LoadElementI {
+                array: a.clone(),
+                index: LValueI::Temp(t1),
+                temp_result: t2,
+            },
+            ThreeAddressCode::StoreElementI {
+                array: a.clone(),
+                index: LValueI::Temp(t1),
+                value: LValueI::Temp(t3),
+            },
+        ];
+
+        let immutable_bb: ImmutableBasicBlock = (bb_label, seq).into();
+
+        // Expected `LivenessDecoratedImmutableBasicBlock`
+        let expected_liveness_decorated_bb = LivenessDecoratedImmutableBasicBlock {
+            label: immutable_bb.label(),
+            seq: vec![
+                LivenessDecoratedThreeAddressCode {
+                    tac: ThreeAddressCode::LoadElementI {
+                        array: a.clone(),
+                        index: LValueI::Temp(t1),
+                        temp_result: t2,
+                    },
+                    liveness_metadata: LivenessMetadata::builder()
+                        .gen_set(hashset! {t1.into()})
+                        .kill_set(hashset! {t2.into()})
+                        .build(),
+                },
+                LivenessDecoratedThreeAddressCode {
+                    tac: ThreeAddressCode::StoreElementI {
+                        array: a,
+                        index: LValueI::Temp(t1),
+                        value: LValueI::Temp(t3),
+                    },
+                    liveness_metadata: LivenessMetadata::builder()
+                        .gen_set(hashset! {t1.into(), t3.into()})
+                        .build(),
+                },
+            ],
+        };
+
+        // Actual `LivenessDecoratedImmutableBasicBlock`
+        let actual_liveness_decorated_bb: LivenessDecoratedImmutableBasicBlock =
+            immutable_bb.into();
+        assert_eq!(expected_liveness_decorated_bb, actual_liveness_decorated_bb);
+    }
+
     #[test]
     #[serial]
     fn call_instruction_gens_all_globals() {
@@ -757,6 +875,7 @@ mod test {
             ReturnType::Void,
             vec![],
             vec![],
+            Span::default(),
         )));
 
         let seq = vec![ThreeAddressCode::Jsr(function_ident.clone())];
@@ -825,12 +944,12 @@ mod test {
             ThreeAddressCode::MulI {
                 lhs: LValueI::Id(b.clone()),
                 rhs: LValueI::Id(c.clone()),
-                temp_result: t1,
+                result: LValueI::Temp(t1),
             },
             ThreeAddressCode::AddI {
                 lhs: LValueI::Temp(t1),
                 rhs: LValueI::Id(a.clone()),
-                temp_result: t2,
+                result: LValueI::Temp(t2),
             },
             ThreeAddressCode::StoreI {
                 lhs: LValueI::Id(d.clone()),
@@ -848,7 +967,7 @@ mod test {
                     tac: ThreeAddressCode::MulI {
                         lhs: LValueI::Id(b.clone()),
                         rhs: LValueI::Id(c.clone()),
-                        temp_result: t1,
+                        result: LValueI::Temp(t1),
                     },
                     liveness_metadata: LivenessMetadata::builder()
                         .gen_set({
@@ -868,7 +987,7 @@ mod test {
                     tac: ThreeAddressCode::AddI {
                         lhs: LValueI::Temp(t1),
                         rhs: LValueI::Id(a.clone()),
-                        temp_result: t2,
+                        result: LValueI::Temp(t2),
                     },
                     liveness_metadata: LivenessMetadata::builder()
                         .gen_set({
@@ -966,6 +1085,7 @@ mod test {
             ReturnType::Void,
             vec![],
             vec![],
+            Span::default(),
         )));
         let (t1, t2, t3, t4, t5, t6): (TempI, TempI, TempI, TempI, TempI, TempI) =
             (1.into(), 2.into(), 3.into(), 4.into(), 5.into(), 6.into());
@@ -1099,7 +1219,7 @@ mod test {
                         tac: MulI {
                             lhs: LValueI::Id(a.clone()),
                             rhs: LValueI::Id(b.clone()),
-                            temp_result: t3,
+                            result: LValueI::Temp(t3),
                         },
                         liveness_metadata: LivenessMetadata::builder()
                             .gen_set({
@@ -1121,6 +1241,8 @@ mod test {
                             })
                             .out_set({
                                 let mut out = HashSet::new();
+                                out.insert(LValue::LValueI(LValueI::Id(a.clone())));
+                                out.insert(LValue::LValueI(LValueI::Id(b.clone())));
                                 out.insert(LValue::LValueI(LValueI::Temp(t3)));
                                 out
                             })
@@ -1146,10 +1268,14 @@ mod test {
                             .in_set({
                                 let mut in_set = HashSet::new();
                                 in_set.insert(LValue::LValueI(LValueI::Temp(t3)));
+                                in_set.insert(LValue::LValueI(LValueI::Id(a.clone())));
+                                in_set.insert(LValue::LValueI(LValueI::Id(b.clone())));
                                 in_set
                             })
                             .out_set({
                                 let mut out = HashSet::new();
+                                out.insert(LValue::LValueI(LValueI::Id(a.clone())));
+                                out.insert(LValue::LValueI(LValueI::Id(b.clone())));
                                 out.insert(LValue::LValueI(LValueI::Id(p.clone())));
                                 out
                             })
@@ -1169,13 +1295,17 @@ mod test {
                             })
                             .in_set({
                                 let mut in_set = HashSet::new();
+                                in_set.insert(LValue::LValueI(LValueI::Id(a.clone())));
+                                in_set.insert(LValue::LValueI(LValueI::Id(b.clone())));
                                 in_set.insert(LValue::LValueI(LValueI::Id(p.clone())));
                                 in_set
                             })
                             .out_set({
                                 let mut out = HashSet::new();
-                                out.insert(LValue::LValueI(LValueI::Temp(t4)));
+                                out.insert(LValue::LValueI(LValueI::Id(a.clone())));
+                                out.insert(LValue::LValueI(LValueI::Id(b.clone())));
                                 out.insert(LValue::LValueI(LValueI::Id(p.clone())));
+                                out.insert(LValue::LValueI(LValueI::Temp(t4)));
                                 out
                             })
                             .build(),
@@ -1196,10 +1326,19 @@ mod test {
                             })
                             .in_set({
                                 let mut in_set = HashSet::new();
+                                in_set.insert(LValue::LValueI(LValueI::Id(a.clone())));
+                                in_set.insert(LValue::LValueI(LValueI::Id(b.clone())));
                                 in_set.insert(LValue::LValueI(LValueI::Id(p.clone())));
                                 in_set.insert(LValue::LValueI(LValueI::Temp(t4)));
                                 in_set
                             })
+                            .out_set({
+                                let mut out = HashSet::new();
+                                out.insert(LValue::LValueI(LValueI::Id(a.clone())));
+                                out.insert(LValue::LValueI(LValueI::Id(b.clone())));
+                                out.insert(LValue::LValueI(LValueI::Id(p.clone())));
+                                out
+                            })
                             .build(),
                     },
                 ],
@@ -1224,8 +1363,18 @@ mod test {
                                 kill.insert(LValue::LValueI(LValueI::Temp(t5)));
                                 kill
                             })
+                            .in_set({
+                                let mut in_set = HashSet::new();
+                                in_set.insert(LValue::LValueI(LValueI::Id(a.clone())));
+                                in_set.insert(LValue::LValueI(LValueI::Id(b.clone())));
+                                in_set.insert(LValue::LValueI(LValueI::Id(p.clone())));
+                                in_set
+                            })
                             .out_set({
                                 let mut out = HashSet::new();
+                                out.insert(LValue::LValueI(LValueI::Id(a.clone())));
+                                out.insert(LValue::LValueI(LValueI::Id(b.clone())));
+                                out.insert(LValue::LValueI(LValueI::Id(p.clone())));
                                 out.insert(LValue::LValueI(LValueI::Temp(t5)));
                                 out
                             })
@@ -1250,11 +1399,17 @@ mod test {
                             })
                             .in_set({
                                 let mut in_set = HashSet::new();
+                                in_set.insert(LValue::LValueI(LValueI::Id(a.clone())));
+                                in_set.insert(LValue::LValueI(LValueI::Id(b.clone())));
+                                in_set.insert(LValue::LValueI(LValueI::Id(p.clone())));
                                 in_set.insert(LValue::LValueI(LValueI::Temp(t5)));
                                 in_set
                             })
                             .out_set({
                                 let mut out = HashSet::new();
+                                out.insert(LValue::LValueI(LValueI::Id(a.clone())));
+                                out.insert(LValue::LValueI(LValueI::Id(b.clone())));
+                                out.insert(LValue::LValueI(LValueI::Id(p.clone())));
                                 out.insert(LValue::LValueI(LValueI::Id(i.clone())));
                                 out
                             })
@@ -1266,11 +1421,17 @@ mod test {
                         liveness_metadata: LivenessMetadata::builder()
                             .in_set({
                                 let mut in_set = HashSet::new();
+                                in_set.insert(LValue::LValueI(LValueI::Id(a.clone())));
+                                in_set.insert(LValue::LValueI(LValueI::Id(b.clone())));
+                                in_set.insert(LValue::LValueI(LValueI::Id(p.clone())));
                                 in_set.insert(LValue::LValueI(LValueI::Id(i.clone())));
                                 in_set
                             })
                             .out_set({
                                 let mut out = HashSet::new();
+                                out.insert(LValue::LValueI(LValueI::Id(a.clone())));
+                                out.insert(LValue::LValueI(LValueI::Id(b.clone())));
+                                out.insert(LValue::LValueI(LValueI::Id(p.clone())));
                                 out.insert(LValue::LValueI(LValueI::Id(i.clone())));
                                 out
                             })
@@ -1289,7 +1450,22 @@ mod test {
                     // LABEL label1
                     LivenessDecoratedThreeAddressCode {
                         tac: Label(tac_label1),
-                        liveness_metadata: LivenessMetadata::builder().build(),
+                        liveness_metadata: LivenessMetadata::builder()
+                            .in_set({
+                                let mut in_set = HashSet::new();
+                                in_set.insert(LValue::LValueI(LValueI::Id(a.clone())));
+                                in_set.insert(LValue::LValueI(LValueI::Id(b.clone())));
+                                in_set.insert(LValue::LValueI(LValueI::Id(p.clone())));
+                                in_set
+                            })
+                            .out_set({
+                                let mut out = HashSet::new();
+                                out.insert(LValue::LValueI(LValueI::Id(a.clone())));
+                                out.insert(LValue::LValueI(LValueI::Id(b.clone())));
+                                out.insert(LValue::LValueI(LValueI::Id(p.clone())));
+                                out
+                            })
+                            .build(),
                     },
                     // STOREI 24 $T6
                     LivenessDecoratedThreeAddressCode {
@@ -1303,8 +1479,18 @@ mod test {
                                 kill.insert(LValue::LValueI(LValueI::Temp(t6)));
                                 kill
                             })
+                            .in_set({
+                                let mut in_set = HashSet::new();
+                                in_set.insert(LValue::LValueI(LValueI::Id(a.clone())));
+                                in_set.insert(LValue::LValueI(LValueI::Id(b.clone())));
+                                in_set.insert(LValue::LValueI(LValueI::Id(p.clone())));
+                                in_set
+                            })
                             .out_set({
                                 let mut out = HashSet::new();
+                                out.insert(LValue::LValueI(LValueI::Id(a.clone())));
+                                out.insert(LValue::LValueI(LValueI::Id(b.clone())));
+                                out.insert(LValue::LValueI(LValueI::Id(p.clone())));
                                 out.insert(LValue::LValueI(LValueI::Temp(t6)));
                                 out
                             })
@@ -1329,11 +1515,17 @@ mod test {
                             })
                             .in_set({
                                 let mut in_set = HashSet::new();
+                                in_set.insert(LValue::LValueI(LValueI::Id(a.clone())));
+                                in_set.insert(LValue::LValueI(LValueI::Id(b.clone())));
+                                in_set.insert(LValue::LValueI(LValueI::Id(p.clone())));
                                 in_set.insert(LValue::LValueI(LValueI::Temp(t6)));
                                 in_set
                             })
                             .out_set({
                                 let mut out = HashSet::new();
+                                out.insert(LValue::LValueI(LValueI::Id(a.clone())));
+                                out.insert(LValue::LValueI(LValueI::Id(b.clone())));
+                                out.insert(LValue::LValueI(LValueI::Id(p.clone())));
                                 out.insert(LValue::LValueI(LValueI::Id(i.clone())));
                                 out
                             })
@@ -1345,11 +1537,17 @@ mod test {
                         liveness_metadata: LivenessMetadata::builder()
                             .in_set({
                                 let mut in_set = HashSet::new();
+                                in_set.insert(LValue::LValueI(LValueI::Id(a.clone())));
+                                in_set.insert(LValue::LValueI(LValueI::Id(b.clone())));
+                                in_set.insert(LValue::LValueI(LValueI::Id(p.clone())));
                                 in_set.insert(LValue::LValueI(LValueI::Id(i.clone())));
                                 in_set
                             })
                             .out_set({
                                 let mut out = HashSet::new();
+                                out.insert(LValue::LValueI(LValueI::Id(a.clone())));
+                                out.insert(LValue::LValueI(LValueI::Id(b.clone())));
+                                out.insert(LValue::LValueI(LValueI::Id(p.clone())));
                                 out.insert(LValue::LValueI(LValueI::Id(i.clone())));
                                 out
                             })
@@ -1371,11 +1569,17 @@ mod test {
                         liveness_metadata: LivenessMetadata::builder()
                             .in_set({
                                 let mut in_set = HashSet::new();
+                                in_set.insert(LValue::LValueI(LValueI::Id(a.clone())));
+                                in_set.insert(LValue::LValueI(LValueI::Id(b.clone())));
+                                in_set.insert(LValue::LValueI(LValueI::Id(p.clone())));
                                 in_set.insert(LValue::LValueI(LValueI::Id(i.clone())));
                                 in_set
                             })
                             .out_set({
                                 let mut out = HashSet::new();
+                                out.insert(LValue::LValueI(LValueI::Id(a.clone())));
+                                out.insert(LValue::LValueI(LValueI::Id(b.clone())));
+                                out.insert(LValue::LValueI(LValueI::Id(p.clone())));
                                 out.insert(LValue::LValueI(LValueI::Id(i.clone())));
                                 out
                             })
@@ -1384,7 +1588,7 @@ mod test {
                     // WRITEI i
                     LivenessDecoratedThreeAddressCode {
                         tac: WriteI {
-                            identifier: i.clone(),
+                            value: LValueI::Id(i.clone()),
                         },
                         liveness_metadata: LivenessMetadata::builder()
                             .gen_set({
@@ -1394,9 +1598,42 @@ mod test {
                             })
                             .in_set({
                                 let mut in_set = HashSet::new();
+                                in_set.insert(LValue::LValueI(LValueI::Id(a.clone())));
+                                in_set.insert(LValue::LValueI(LValueI::Id(b.clone())));
+                                in_set.insert(LValue::LValueI(LValueI::Id(p.clone())));
+                                in_set.insert(LValue::LValueI(LValueI::Id(i.clone())));
+                                in_set
+                            })
+                            .out_set({
+                                let mut out = HashSet::new();
+                                out.insert(LValue::LValueI(LValueI::Id(a.clone())));
+                                out.insert(LValue::LValueI(LValueI::Id(b.clone())));
+                                out.insert(LValue::LValueI(LValueI::Id(p.clone())));
+                                out.insert(LValue::LValueI(LValueI::Id(i.clone())));
+                                out
+                            })
+                            .build(),
+                    },
+                    // RET
+                    LivenessDecoratedThreeAddressCode {
+                        tac: Ret,
+                        liveness_metadata: LivenessMetadata::builder()
+                            .in_set({
+                                let mut in_set = HashSet::new();
+                                in_set.insert(LValue::LValueI(LValueI::Id(a.clone())));
+                                in_set.insert(LValue::LValueI(LValueI::Id(b.clone())));
+                                in_set.insert(LValue::LValueI(LValueI::Id(p.clone())));
                                 in_set.insert(LValue::LValueI(LValueI::Id(i.clone())));
                                 in_set
                             })
+                            .out_set({
+                                let mut out = HashSet::new();
+                                out.insert(LValue::LValueI(LValueI::Id(a.clone())));
+                                out.insert(LValue::LValueI(LValueI::Id(b.clone())));
+                                out.insert(LValue::LValueI(LValueI::Id(p.clone())));
+                                out.insert(LValue::LValueI(LValueI::Id(i.clone())));
+                                out
+                            })
                             .build(),
                     },
                 ],
@@ -1432,31 +1669,32 @@ mod test {
             ```
             ==== Basic Blocks ===
             BB0:
-            LABEL main              | GEN:            | KILL:           | IN:           | OUT:
-            LINK                    | GEN:            | KILL:           | IN:           | OUT:
-            STOREI 4 $T1            | GEN:            | KILL: $T1,      | IN:           | OUT: $T1,
-            STOREI $T1 a            | GEN: $T1,       | KILL: a,        | IN: $T1,      | OUT: a,
-            STOREI 2 $T2            | GEN:            | KILL: $T2,      | IN: a,        | OUT: a, $T2,
-            STOREI $T2 b            | GEN: $T2,       | KILL: b,        | IN: $T2, a,   | OUT: b, a,
-            MULTI a b $T3           | GEN: b, a,      | KILL: $T3,      | IN: a, b,     | OUT: $T3,
-            STOREI $T3 p            | GEN: $T3,       | KILL: p,        | IN: $T3,      | OUT: p,
-            STOREI 10 $T4           | GEN:            | KILL: $T4,      | IN: p,        | OUT: $T4, p,
-            LE p $T4 label1         | GEN: p, $T4,    | KILL:           | IN: p, $T4,   | OUT:
+            LABEL main              | GEN:            | KILL:           | IN:                   | OUT:
+            LINK                    | GEN:            | KILL:           | IN:                   | OUT:
+            STOREI 4 $T1            | GEN:            | KILL: $T1,      | IN:                   | OUT: $T1,
+            STOREI $T1 a            | GEN: $T1,       | KILL: a,        | IN: $T1,              | OUT: a,
+            STOREI 2 $T2            | GEN:            | KILL: $T2,      | IN: a,                | OUT: a, $T2,
+            STOREI $T2 b            | GEN: $T2,       | KILL: b,        | IN: $T2, a,           | OUT: a, b,
+            MULTI a b $T3           | GEN: b, a,      | KILL: $T3,      | IN: a, b,             | OUT: a, b, $T3,
+            STOREI $T3 p            | GEN: $T3,       | KILL: p,        | IN: $T3, a, b,        | OUT: a, b, p,
+            STOREI 10 $T4           | GEN:            | KILL: $T4,      | IN: a, b, p,          | OUT: a, b, p, $T4,
+            LE p $T4 label1         | GEN: p, $T4,    | KILL:           | IN: a, b, p, $T4,     | OUT: a, b, p,
 
             BB1:
-            STOREI 42 $T5           | GEN:            | KILL: $T5,      | IN:           | OUT: $T5,
-            STOREI $T5 i            | GEN: $T5,       | KILL: i,        | IN: $T5,      | OUT: i,
-            JUMP label2             | GEN:            | KILL:           | IN: i,        | OUT: i,
+            STOREI 42 $T5           | GEN:            | KILL: $T5,      | IN: a, b, p,          | OUT: a, b, p, $T5,
+            STOREI $T5 i            | GEN: $T5,       | KILL: i,        | IN: a, b, p, $T5,     | OUT: a, b, p, i,
+            JUMP label2             | GEN:            | KILL:           | IN: a, b, p, i,       | OUT: a, b, p, i,
 
             BB2:
-            LABEL label1            | GEN:            | KILL:           | IN:           | OUT:
-            STOREI 24 $T6           | GEN:            | KILL: $T6,      | IN:           | OUT: $T6,
-            STOREI $T6 i            | GEN: $T6,       | KILL: i,        | IN: $T6,      | OUT: i,
-            JUMP label2             | GEN:            | KILL:           | IN: i,        | OUT: i,
+            LABEL label1            | GEN:            | KILL:           | IN: a, b, p,          | OUT: a, b, p,
+            STOREI 24 $T6           | GEN:            | KILL: $T6,      | IN: a, b, p,          | OUT: a, b, p, $T6,
+            STOREI $T6 i            | GEN: $T6,       | KILL: i,        | IN: a, b, p, $T6,     | OUT: a, b, p, i,
+            JUMP label2             | GEN:            | KILL:           | IN: a, b, p, i,       | OUT: a, b, p, i,
 
             BB3:
-            LABEL label2            | GEN:            | KILL:           | IN: i,        | OUT: i,
-            WRITEI i                | GEN: i,         | KILL:           | IN: i,        | OUT:
+            LABEL label2            | GEN:            | KILL:           | IN: a, b, p, i,       | OUT: a, b, p, i,
+            WRITEI i                | GEN: i,         | KILL:           | IN: a, b, p, i,       | OUT: a, b, p, i,
+            RET                     | GEN:            | KILL:           | IN: a, b, p, i,       | OUT: a, b, p, i,
 
             ==== CFG ===
             BB0: [BBLabel(2), BBLabel(1)]
@@ -1470,5 +1708,110 @@ mod test {
         assert_eq!(expected_cfg, cfg);
     }
 
+    #[test]
+    #[serial]
+    fn temp_written_in_both_branches_is_live_out_of_both() {
+        reset_label_counter();
+
+        let program = r"
+            PROGRAM sample
+            BEGIN
+                INT a, b, c;
+
+                FUNCTION VOID main()
+                BEGIN
+                    c := IF (a > b) THEN a ELSE b FI;
+                END
+            END
+        ";
+
+        let mut result = microc::ProgramParser::new().parse(program).unwrap();
+        let mut visitor = ThreeAddressCodeVisitor;
+        let cfg: LivenessDecoratedControlFlowGraph =
+            Into::<ControlFlowGraph>::into(Into::<BBFunction>::into(
+                visitor.walk_ast(result.remove(0)),
+            ))
+            .into();
+
+        /*
+            BB0: LABEL main, LINK, LE a b label1
+            BB1: STOREI a $T1, JUMP label2
+            BB2: LABEL label1, STOREI b $T1
+            BB3: LABEL label2, STOREI $T1 c, RET
+        */
+        let t1 = LValue::LValueI(LValueI::Temp(1.into()));
+        let bbs: Vec<&LivenessDecoratedImmutableBasicBlock> =
+            cfg.basic_blocks().map(|(_, bb)| bb).collect();
+        assert_eq!(4, bbs.len());
+
+        // Live out of both branches, as either may reach the join...
+        assert!(bbs[1]
+            .seq()
+            .last()
+            .unwrap()
+            .out_set()
+            .borrow()
+            .contains(&t1));
+        assert!(bbs[2]
+            .seq()
+            .last()
+            .unwrap()
+            .out_set()
+            .borrow()
+            .contains(&t1));
+        assert!(bbs[3].in_set().borrow().contains(&t1));
+        // ...but not before either branch defines it.
+        assert!(!bbs[0].in_set().borrow().contains(&t1));
+        assert!(!bbs[0]
+            .seq()
+            .last()
+            .unwrap()
+            .out_set()
+            .borrow()
+            .contains(&t1));
+    }
+
+    #[test]
+    #[serial]
+    fn var_param_is_live_out_of_return() {
+        let program = r"
+            PROGRAM sample
+            BEGIN
+                FUNCTION VOID add(VAR INT x, INT y)
+                BEGIN
+                    x := x + y;
+                    y := 0;
+                END
+            END
+        ";
+
+        let mut result = microc::ProgramParser::new().parse(program).unwrap();
+        let mut visitor = ThreeAddressCodeVisitor;
+        let cfg: LivenessDecoratedControlFlowGraph =
+            Into::<ControlFlowGraph>::into(Into::<BBFunction>::into(
+                visitor.walk_ast(result.remove(0)),
+            ))
+            .into();
+
+        let param = |index| {
+            LValue::LValueI(LValueI::Id(IdentI(data::Symbol::FunctionScopedSymbol(
+                Rc::new(data::FunctionScopedSymbol::Int {
+                    symbol_type: data::FunctionScopedSymbolType::Parameter(2),
+                    index,
+                }),
+            ))))
+        };
+        let ret = cfg
+            .basic_blocks()
+            .flat_map(|(_, bb)| bb.seq())
+            .find(|tac| tac.tac().is_return())
+            .unwrap();
+
+        // The caller copies the value of `x` back on return,
+        // while the value of `y` is discarded.
+        assert!(ret.out_set().borrow().contains(&param(1)));
+        assert!(!ret.out_set().borrow().contains(&param(2)));
+    }
+
     // TODO: Add unit test for a program with a loop
 }
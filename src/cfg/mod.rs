@@ -1,8 +1,5 @@
 use crate::cfg::basic_block::{BBFunction, BBLabel, ImmutableBasicBlock};
-use crate::three_addr_code_ir::three_address_code::ThreeAddressCode;
-use crate::three_addr_code_ir::{LValueF, LValueI};
 use linked_hash_map::LinkedHashMap;
-use std::collections::HashSet;
 use std::fmt::{Display, Formatter};
 
 pub mod basic_block;
@@ -93,7 +90,10 @@ impl From<BBFunction> for ControlFlowGraph {
                 }
             }
             prev_bb_label.replace(*bb_label);
-            prev_bb_has_unconditional_jump = last_tac.is_unconditional_branch();
+            // Control never falls through a return
+            // either, a returning block has no successors.
+            prev_bb_has_unconditional_jump =
+                last_tac.is_unconditional_branch() || last_tac.is_return();
 
             // Create an edge to the explicit jump/branch target
             // of the current basic block.
@@ -114,12 +114,11 @@ impl From<BBFunction> for ControlFlowGraph {
 mod test {
     use crate::cfg::basic_block::{BBFunction, BBLabel};
     use crate::cfg::ControlFlowGraph;
+    use crate::span::Span;
     use crate::symbol_table::symbol::function::ReturnType;
     use crate::symbol_table::symbol::{data, function};
     use crate::three_addr_code_ir;
-    use crate::three_addr_code_ir::three_address_code::visit::{
-        CodeObject, ThreeAddressCodeVisitor,
-    };
+    use crate::three_addr_code_ir::three_address_code::visit::ThreeAddressCodeVisitor;
     use crate::three_addr_code_ir::three_address_code::ThreeAddressCode;
     use crate::three_addr_code_ir::three_address_code::ThreeAddressCode::{AddF, DivF, EqI, FunctionLabel, Jump, Label, Link, LteI, MulF, MulI, StoreI, SubI, WriteF, WriteI, StoreF};
     use crate::three_addr_code_ir::{
@@ -188,6 +187,7 @@ mod test {
             ReturnType::Void,
             vec![],
             vec![],
+            Span::default(),
         )));
         let (t1, t2, t3, t4, t5, t6): (TempI, TempI, TempI, TempI, TempI, TempI) =
             (1.into(), 2.into(), 3.into(), 4.into(), 5.into(), 6.into());
@@ -230,7 +230,7 @@ mod test {
                     MulI {
                         lhs: LValueI::Id(a.clone()),
                         rhs: LValueI::Id(b.clone()),
-                        temp_result: t3,
+                        result: LValueI::Temp(t3),
                     },
                     // STOREI $T3 p
                     StoreI {
@@ -307,7 +307,11 @@ mod test {
                     // LABEL label2
                     Label(tac_label2),
                     // WRITEI i
-                    WriteI { identifier: i },
+                    WriteI {
+                        value: LValueI::Id(i),
+                    },
+                    // RET
+                    ThreeAddressCode::Ret,
                 ],
             )
                 .into(),
@@ -363,6 +367,7 @@ mod test {
             BB3:
             LABEL label2
             WRITEI i
+            RET
 
             ==== CFG ===
             BB0: [BBLabel(2), BBLabel(1)]
@@ -434,6 +439,7 @@ mod test {
             ReturnType::Void,
             vec![],
             vec![],
+            Span::default(),
         )));
         let (t1, t2, t3, t4, t5, t6, t7, t8, t9, t10): (
             TempF,
@@ -563,19 +569,19 @@ mod test {
                     DivF {
                         lhs: LValueF::Id(num.clone()),
                         rhs: LValueF::Id(approx.clone()),
-                        temp_result: t8,
+                        result: LValueF::Temp(t8),
                     },
                     // ADDF approx $T8 $T9
                     AddF {
                         lhs: LValueF::Id(approx.clone()),
                         rhs: LValueF::Temp(t8),
-                        temp_result: t9,
+                        result: LValueF::Temp(t9),
                     },
                     // MULTF $T7 $T9 $T10
                     MulF {
                         lhs: LValueF::Temp(t7),
                         rhs: LValueF::Temp(t9),
-                        temp_result: t10,
+                        result: LValueF::Temp(t10),
                     },
                     // STOREF $T10 newapprox
                     StoreF {
@@ -608,7 +614,7 @@ mod test {
                     SubI {
                         lhs: LValueI::Id(i.clone()),
                         rhs: LValueI::Temp(t5),
-                        temp_result: t6,
+                        result: LValueI::Temp(t6),
                     },
                     // STOREI $T6 i
                     StoreI {
@@ -631,8 +637,10 @@ mod test {
                     Label(tac_label2),
                     // WRITEF approx
                     WriteF {
-                        identifier: approx.clone(),
+                        value: LValueF::Id(approx.clone()),
                     },
+                    // RET
+                    ThreeAddressCode::Ret,
                 ],
             )
                 .into(),
@@ -695,6 +703,7 @@ mod test {
             BB4:
             LABEL label2
             WRITEF approx
+            RET
 
 
             ==== CFG ===
@@ -709,4 +718,153 @@ mod test {
 
         assert_eq!(expected_cfg, cfg);
     }
+
+    #[test]
+    #[serial]
+    fn bb_function_with_do_while_loop_to_cfg() {
+        reset_label_counter();
+
+        let program = r"
+            PROGRAM test
+            BEGIN
+                INT i;
+                FLOAT approx,num;
+
+                FUNCTION VOID main()
+                BEGIN
+                    num := 7.0;
+                    i := 100;
+                    approx := num;
+
+                    DO
+                        approx := 0.5*(approx + num/approx);
+                        i := i-1;
+                    WHILE (i != 0);
+
+                    WRITE(approx);
+                END
+            END
+        ";
+
+        let (bb_label0, bb_label1, bb_label2, bb_label3, bb_label4): (
+            BBLabel,
+            BBLabel,
+            BBLabel,
+            BBLabel,
+            BBLabel,
+        ) = (0.into(), 1.into(), 2.into(), 3.into(), 4.into());
+
+        /*
+            Expected control flow graph -
+            ```
+            BB0: LABEL main ... STOREF num approx
+            BB1: LABEL label1 (loop body)
+            BB2: LABEL label3 (loop condition) ... EQ i $T label2
+            BB3: JUMP label1
+            BB4: LABEL label2 WRITEF approx
+
+            ==== CFG ===
+            BB0: [BBLabel(1)]
+            BB1: [BBLabel(2)]
+            BB2: [BBLabel(4), BBLabel(3)]
+            BB3: [BBLabel(1)]
+            ```
+        */
+        let mut expected_bb_map = LinkedHashMap::new();
+        expected_bb_map.insert(bb_label0, vec![bb_label1]);
+        expected_bb_map.insert(bb_label1, vec![bb_label2]);
+        expected_bb_map.insert(bb_label2, vec![bb_label4, bb_label3]);
+        expected_bb_map.insert(bb_label3, vec![bb_label1]);
+
+        // Parse program, generate 3AC, convert it into a `BBFunction` and convert `BBFunction` to a `ControlFlowGraph`
+        let program = microc::ProgramParser::new().parse(program);
+        let mut result = program.unwrap();
+        let mut visitor = ThreeAddressCodeVisitor;
+        result.reverse();
+        let cfg = result
+            .into_iter()
+            .map(|ast_node| visitor.walk_ast(ast_node))
+            .map(Into::<BBFunction>::into)
+            .map(Into::<ControlFlowGraph>::into)
+            .last()
+            .unwrap();
+
+        assert_eq!(expected_bb_map, cfg.bb_map);
+    }
+
+    #[test]
+    #[serial]
+    fn bb_function_with_break_and_continue_to_cfg() {
+        reset_label_counter();
+
+        let program = r"
+            PROGRAM test
+            BEGIN
+                INT i;
+
+                FUNCTION VOID main()
+                BEGIN
+                    FOR (i := 0; i < 10; i := i + 1)
+                        IF (i = 5)
+                            BREAK;
+                        FI
+                        CONTINUE;
+                    ROF
+
+                    WRITE(i);
+                END
+            END
+        ";
+
+        let bb_labels: Vec<BBLabel> = (0..9).map(Into::into).collect();
+
+        /*
+            Expected control flow graph -
+            ```
+            BB0: LABEL main ... STOREI $T1 i
+            BB1: LABEL label1 (loop condition) ... GE i $T2 label2
+            BB2: STOREI 5 $T3 NE i $T3 label4
+            BB3: JUMP label2 (BREAK)
+            BB4: JUMP label5 (unreachable, no predecessors)
+            BB5: LABEL label4
+            BB6: LABEL label5 JUMP label3 (CONTINUE)
+            BB7: LABEL label3 (loop incr) ... JUMP label1
+            BB8: LABEL label2 WRITEI i RET
+
+            ==== CFG ===
+            BB0: [BBLabel(1)]
+            BB1: [BBLabel(8), BBLabel(2)]
+            BB2: [BBLabel(5), BBLabel(3)]
+            BB3: [BBLabel(8)]
+            BB4: [BBLabel(6)]
+            BB5: [BBLabel(6)]
+            BB6: [BBLabel(7)]
+            BB7: [BBLabel(1)]
+            ```
+        */
+        let mut expected_bb_map = LinkedHashMap::new();
+        expected_bb_map.insert(bb_labels[0], vec![bb_labels[1]]);
+        expected_bb_map.insert(bb_labels[1], vec![bb_labels[8], bb_labels[2]]);
+        expected_bb_map.insert(bb_labels[2], vec![bb_labels[5], bb_labels[3]]);
+        expected_bb_map.insert(bb_labels[3], vec![bb_labels[8]]);
+        expected_bb_map.insert(bb_labels[4], vec![bb_labels[6]]);
+        expected_bb_map.insert(bb_labels[5], vec![bb_labels[6]]);
+        expected_bb_map.insert(bb_labels[6], vec![bb_labels[7]]);
+        expected_bb_map.insert(bb_labels[7], vec![bb_labels[1]]);
+
+        // Parse program, generate 3AC, convert it into a `BBFunction` and convert `BBFunction` to a `ControlFlowGraph`
+        let program = microc::ProgramParser::new().parse(program);
+        let mut result = program.unwrap();
+        let mut visitor = ThreeAddressCodeVisitor;
+        result.reverse();
+        let cfg = result
+            .into_iter()
+            .map(|ast_node| visitor.walk_ast(ast_node))
+            .map(Into::<BBFunction>::into)
+            .map(Into::<ControlFlowGraph>::into)
+            .last()
+            .unwrap();
+
+        assert_eq!(expected_bb_map, cfg.bb_map);
+    }
 }
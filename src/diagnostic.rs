@@ -0,0 +1,285 @@
+use crate::span::{LineMap, Span};
+use std::fmt::{Display, Formatter};
+
+/// How serious the problem reported
+/// by a diagnostic is. Only errors stop
+/// the program from being compiled.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, derive_more::Display)]
+pub enum Severity {
+    #[display(fmt = "error")]
+    Error,
+    #[display(fmt = "warning")]
+    Warning,
+}
+
+/// Identifies the kind of problem a diagnostic
+/// reports. Codes starting with `E00` are syntax
+/// errors, `E01` are errors in the use of symbols
+/// and `E02` are semantic errors. Warnings use `W`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Code {
+    /// The input contains text that is not a token.
+    InvalidToken,
+    /// A token appears where the grammar does not allow it.
+    UnexpectedToken,
+    /// The input ends in the middle of the program.
+    UnexpectedEof,
+    /// The input goes on after the end of the program.
+    ExtraToken,
+    /// A symbol is declared more than once in a scope.
+    DuplicateDeclaration,
+    /// A symbol is used without being declared.
+    UndeclaredSymbol,
+    /// A symbol is declared in a scope it cannot be declared in.
+    InvalidScope,
+    /// The result of a VOID function is used as a value.
+    VoidValue,
+    /// A function is defined differently from its prototype.
+    PrototypeMismatch,
+    /// A function declared by a prototype is called but never defined.
+    UndefinedFunction,
+    /// A value does not have the type its use requires.
+    MismatchedTypes,
+    /// A value that must be known at compile time is not.
+    NotConstant,
+    /// A symbol cannot be assigned, or read, into.
+    InvalidAssignment,
+    /// An array is declared or used incorrectly.
+    InvalidArray,
+    /// A RECORD is declared or used incorrectly.
+    InvalidRecord,
+    /// A BREAK, CONTINUE or RETURN does not fit where it is used.
+    InvalidControlFlow,
+    /// A CASE statement has more than one arm with the same label.
+    DuplicateCaseLabel,
+    /// A numeric literal, or a CASE label, does not fit in its type.
+    LiteralOutOfRange,
+    /// A function is called with more or fewer
+    /// arguments than it has parameters.
+    WrongArgumentCount,
+    /// A function declared by a prototype is never defined.
+    UnusedPrototype,
+    /// A non-VOID function can reach its end
+    /// without returning a value.
+    MissingReturn,
+}
+
+impl Display for Code {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let code = match self {
+            Code::InvalidToken => "E0001",
+            Code::UnexpectedToken => "E0002",
+            Code::UnexpectedEof => "E0003",
+            Code::ExtraToken => "E0004",
+            Code::DuplicateDeclaration => "E0101",
+            Code::UndeclaredSymbol => "E0102",
+            Code::InvalidScope => "E0103",
+            Code::VoidValue => "E0104",
+            Code::PrototypeMismatch => "E0105",
+            Code::UndefinedFunction => "E0106",
+            Code::MismatchedTypes => "E0201",
+            Code::NotConstant => "E0202",
+            Code::InvalidAssignment => "E0203",
+            Code::InvalidArray => "E0204",
+            Code::InvalidRecord => "E0205",
+            Code::InvalidControlFlow => "E0206",
+            Code::DuplicateCaseLabel => "E0207",
+            Code::LiteralOutOfRange => "E0208",
+            Code::WrongArgumentCount => "E0209",
+            Code::UnusedPrototype => "W0001",
+            Code::MissingReturn => "W0002",
+        };
+
+        write!(f, "{}", code)
+    }
+}
+
+/// A message attached to a span of the source.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Label {
+    pub span: Span,
+    pub message: String,
+}
+
+/// A problem found in a program, pointing to
+/// the source it is about. The primary label
+/// marks where the problem is, and secondary
+/// labels mark related source, like an earlier
+/// declaration.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub code: Code,
+    pub message: String,
+    pub primary: Label,
+    pub secondary: Vec<Label>,
+    pub notes: Vec<String>,
+}
+
+impl Diagnostic {
+    pub fn error<T: ToString>(code: Code, message: T, span: Span) -> Self {
+        Self::new(Severity::Error, code, message, span)
+    }
+
+    pub fn warning<T: ToString>(code: Code, message: T, span: Span) -> Self {
+        Self::new(Severity::Warning, code, message, span)
+    }
+
+    fn new<T: ToString>(severity: Severity, code: Code, message: T, span: Span) -> Self {
+        Self {
+            severity,
+            code,
+            message: message.to_string(),
+            primary: Label {
+                span,
+                message: String::new(),
+            },
+            secondary: vec![],
+            notes: vec![],
+        }
+    }
+
+    /// Sets the message under the primary span.
+    pub fn with_label<T: ToString>(mut self, message: T) -> Self {
+        self.primary.message = message.to_string();
+        self
+    }
+
+    pub fn with_secondary<T: ToString>(mut self, span: Span, message: T) -> Self {
+        self.secondary.push(Label {
+            span,
+            message: message.to_string(),
+        });
+        self
+    }
+
+    pub fn with_note<T: ToString>(mut self, note: T) -> Self {
+        self.notes.push(note.to_string());
+        self
+    }
+
+    /// Renders the diagnostic the way rustc does, quoting each
+    /// line of `file_name` that a label points into and marking
+    /// the labelled source with carets, `^` for the primary
+    /// label and `-` for secondary labels.
+    pub fn render(&self, file_name: &str, line_map: &LineMap) -> String {
+        let start = line_map.line_col(self.primary.span.start);
+
+        let mut labels = std::iter::once((&self.primary, '^'))
+            .chain(self.secondary.iter().map(|label| (label, '-')))
+            .map(|(label, marker)| (line_map.line_col(label.span.start), label, marker))
+            .collect::<Vec<_>>();
+        // Labels are quoted in source order, and the primary
+        // label comes first among labels on the same line.
+        labels.sort_by_key(|(line_col, _, marker)| (line_col.line, *marker != '^'));
+
+        let gutter_width = labels
+            .iter()
+            .map(|(line_col, ..)| line_col.line.to_string().len())
+            .max()
+            .unwrap_or(1);
+        let gutter = " ".repeat(gutter_width);
+
+        let mut rendered = format!(
+            "{}[{}]: {}\n{}--> {}:{}\n{} |\n",
+            self.severity, self.code, self.message, gutter, file_name, start, gutter
+        );
+
+        let mut quoted_line = None;
+        for (line_col, label, marker) in labels {
+            if quoted_line != Some(line_col.line) {
+                rendered += &format!(
+                    "{:>width$} | {}\n",
+                    line_col.line,
+                    line_map.line(line_col.line).replace('\t', " "),
+                    width = gutter_width
+                );
+                quoted_line = Some(line_col.line);
+            }
+
+            // A span that goes on past its first
+            // line is only marked up to the end of
+            // it, and an empty span gets one marker.
+            let end = line_map.line_col(label.span.end);
+            let end_col = if end.line == line_col.line {
+                end.col
+            } else {
+                line_map.line(line_col.line).chars().count() + 1
+            };
+            let markers = marker.to_string().repeat((end_col - line_col.col).max(1));
+
+            let underline = format!(
+                "{}{} {}",
+                " ".repeat(line_col.col - 1),
+                markers,
+                label.message
+            );
+            rendered += &format!("{} | {}\n", gutter, underline.trim_end());
+        }
+
+        rendered += &format!("{} |\n", gutter);
+        for note in &self.notes {
+            rendered += &format!("{} = note: {}\n", gutter, note);
+        }
+
+        rendered
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::error::CompileError;
+    use lalrpop_util::ParseError;
+
+    #[test]
+    fn render_marks_primary_and_secondary_labels() {
+        let source = "FUNCTION INT f();\n\nFUNCTION FLOAT f()\nBEGIN\nEND";
+        let line_map = LineMap::new(source);
+        let diagnostic = Diagnostic::error(
+            Code::PrototypeMismatch,
+            "definition of function `f` does not match its prototype",
+            Span::new(19, 37),
+        )
+        .with_label("defined here")
+        .with_secondary(Span::new(0, 16), "declared here")
+        .with_note("the definition must have the same return and parameter types");
+
+        assert_eq!(
+            "error[E0105]: definition of function `f` does not match its prototype
+ --> test.micro:3:1
+  |
+1 | FUNCTION INT f();
+  | ---------------- declared here
+3 | FUNCTION FLOAT f()
+  | ^^^^^^^^^^^^^^^^^^ defined here
+  |
+  = note: the definition must have the same return and parameter types
+",
+            diagnostic.render("test.micro", &line_map)
+        );
+    }
+
+    #[test]
+    fn render_marks_end_of_input_with_single_caret() {
+        let source = "PROGRAM p\nBEGIN";
+        let line_map = LineMap::new(source);
+        let err = CompileError::from(ParseError::UnrecognizedEOF {
+            location: source.len(),
+            expected: vec!["\"END\"".to_owned(), "IDENTIFIER".to_owned()],
+        });
+        let diagnostic = err.diagnostic();
+
+        assert_eq!(
+            "error[E0003]: unexpected end of input
+ --> test.micro:2:6
+  |
+2 | BEGIN
+  |      ^ the program ends here
+  |
+  = note: expected one of `END` or `IDENTIFIER`
+",
+            diagnostic.render("test.micro", &line_map)
+        );
+    }
+}
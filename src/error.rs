@@ -0,0 +1,113 @@
+use crate::diagnostic::{Code, Diagnostic};
+use crate::span::Span;
+use crate::symbol_table::error::SymbolError;
+use lalrpop_util::lexer::Token;
+use lalrpop_util::ParseError;
+use std::fmt::{Display, Formatter};
+
+/// Type representing the errors that stop
+/// a program from being compiled. Each error
+/// carries the diagnostic that reports it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CompileError {
+    /// The program contains text that is not a token.
+    Lexical(Diagnostic),
+    /// The tokens of the program do not
+    /// form a program in the grammar.
+    Syntax(Diagnostic),
+    /// A symbol is declared or used incorrectly.
+    Symbol(SymbolError, Box<Diagnostic>),
+    /// A value does not have the type its use requires.
+    Type(Diagnostic),
+    /// Any other construct that is well formed, but has
+    /// no meaning, e.g., a BREAK outside of a loop.
+    Semantic(Diagnostic),
+}
+
+impl CompileError {
+    pub fn symbol(error: SymbolError, diagnostic: Diagnostic) -> Self {
+        CompileError::Symbol(error, Box::new(diagnostic))
+    }
+
+    pub fn diagnostic(&self) -> &Diagnostic {
+        match self {
+            CompileError::Symbol(_, diagnostic) => diagnostic,
+            CompileError::Lexical(diagnostic)
+            | CompileError::Syntax(diagnostic)
+            | CompileError::Type(diagnostic)
+            | CompileError::Semantic(diagnostic) => diagnostic,
+        }
+    }
+}
+
+impl Display for CompileError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let diagnostic = self.diagnostic();
+        write!(f, "[{}] {}", diagnostic.code, diagnostic.message)
+    }
+}
+
+impl std::error::Error for CompileError {}
+
+impl<'input> From<ParseError<usize, Token<'input>, CompileError>> for CompileError {
+    fn from(err: ParseError<usize, Token<'input>, CompileError>) -> Self {
+        match err {
+            ParseError::InvalidToken { location } => CompileError::Lexical(
+                Diagnostic::error(
+                    Code::InvalidToken,
+                    "unrecognized text",
+                    Span::new(location, location + 1),
+                )
+                .with_label("not a valid token"),
+            ),
+            ParseError::UnrecognizedEOF { location, expected } => CompileError::Syntax(
+                Diagnostic::error(
+                    Code::UnexpectedEof,
+                    "unexpected end of input",
+                    Span::new(location, location),
+                )
+                .with_label("the program ends here")
+                .with_note(expected_tokens(&expected)),
+            ),
+            ParseError::UnrecognizedToken {
+                token: (start, Token(_, text), end),
+                expected,
+            } => CompileError::Syntax(
+                Diagnostic::error(
+                    Code::UnexpectedToken,
+                    format!("unexpected `{}`", text),
+                    Span::new(start, end),
+                )
+                .with_label("unexpected token")
+                .with_note(expected_tokens(&expected)),
+            ),
+            ParseError::ExtraToken {
+                token: (start, Token(_, text), end),
+            } => CompileError::Syntax(
+                Diagnostic::error(
+                    Code::ExtraToken,
+                    format!("unexpected `{}` after the end of the program", text),
+                    Span::new(start, end),
+                )
+                .with_label("expected no more input"),
+            ),
+            ParseError::User { error } => error,
+        }
+    }
+}
+
+/// Lists the tokens the parser expected in a note.
+/// LALRPOP quotes terminals that are written as
+/// string literals in the grammar.
+fn expected_tokens(expected: &[String]) -> String {
+    let expected = expected
+        .iter()
+        .map(|token| format!("`{}`", token.trim_matches('"')))
+        .collect::<Vec<_>>();
+
+    match expected.as_slice() {
+        [] => "expected nothing more".to_owned(),
+        [token] => format!("expected {}", token),
+        [tokens @ .., last] => format!("expected one of {} or {}", tokens.join(", "), last),
+    }
+}
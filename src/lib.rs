@@ -0,0 +1,247 @@
+#![feature(box_into_inner)]
+mod asm;
+mod ast;
+mod cfg;
+pub mod diagnostic;
+pub mod error;
+mod register_alloc;
+pub mod span;
+mod symbol_table;
+mod three_addr_code_ir;
+mod token;
+
+#[cfg(test)]
+#[macro_use]
+extern crate maplit;
+
+#[macro_use]
+extern crate lalrpop_util;
+
+use crate::ast::type_check;
+use crate::cfg::basic_block::BBFunction;
+use crate::symbol_table::SymbolTable;
+use crate::three_addr_code_ir::three_address_code::visit::ThreeAddressCodeVisitor;
+
+use crate::asm::tiny::{TinyCodeSequence, ALLOWED_REGISTERS};
+use crate::cfg::liveness::LivenessDecoratedControlFlowGraph;
+use crate::cfg::ControlFlowGraph;
+use crate::diagnostic::Diagnostic;
+use crate::error::CompileError;
+
+pub use crate::symbol_table::error::SymbolError;
+
+lalrpop_mod!(pub microc);
+
+/// The outcome of compiling a program.
+#[derive(Debug)]
+pub struct Compilation {
+    /// The Tiny code for the program, one instruction
+    /// per line, or the errors that stopped it from
+    /// being compiled, in the order they appear in it.
+    pub result: Result<String, Vec<CompileError>>,
+    /// Problems found in the program that do
+    /// not stop it from being compiled.
+    pub warnings: Vec<Diagnostic>,
+}
+
+/// Compiles the Microc program `source` to Tiny code.
+pub fn compile(source: &str) -> Compilation {
+    SymbolTable::reset();
+
+    let program = microc::ProgramParser::new().parse(source);
+
+    // The parser recovers from syntax errors, and continues past
+    // symbol and semantic errors, so they are reported along with
+    // the error it stopped at, if any.
+    let mut errors = SymbolTable::syntax_errors();
+    errors.extend(SymbolTable::symbol_errors());
+    let program = match program {
+        Ok(mut program) => {
+            // Functions are parsed right to left.
+            program.reverse();
+
+            // Type errors are only found once the whole program
+            // has been parsed, so they are merged into the other
+            // errors in the order they appear in the program.
+            errors.extend(type_check::check_program(&mut program));
+            errors.sort_by_key(|error| error.diagnostic().primary.span.start);
+
+            if errors.is_empty() {
+                Ok(program)
+            } else {
+                Err(errors)
+            }
+        }
+        Err(err) => {
+            errors.push(err.into());
+            errors.sort_by_key(|error| error.diagnostic().primary.span.start);
+            Err(errors)
+        }
+    };
+    let warnings = SymbolTable::warnings();
+
+    let result = match program {
+        Ok(program) => program,
+        Err(errors) => {
+            return Compilation {
+                result: Err(errors),
+                warnings,
+            }
+        }
+    };
+
+    let mut visitor = ThreeAddressCodeVisitor;
+    let num_functions = result.len();
+
+    let tiny_code = result
+        .into_iter()
+        .enumerate()
+        .map(|(i, ast_node)| (i, visitor.walk_ast(ast_node)))
+        .map(|(i, code_object)| {
+            (
+                i,
+                ControlFlowGraph::from(Into::<BBFunction>::into(code_object)),
+            )
+        })
+        .map(|(i, cfg)| (i, LivenessDecoratedControlFlowGraph::from(cfg)))
+        .map(|(i, cfg)| {
+            (
+                i,
+                register_alloc::perform_register_allocation(cfg, ALLOWED_REGISTERS),
+            )
+        })
+        .map(|(i, (register_alloc_tacs, stack_space_consumed))| {
+            let (is_first, is_last) = (i == 0, i == num_functions - 1);
+            Into::<TinyCodeSequence>::into((
+                register_alloc_tacs,
+                stack_space_consumed,
+                is_first,
+                is_last,
+            ))
+        })
+        .flat_map(|tiny_code_seq| tiny_code_seq.sequence)
+        .map(|code| format!("{code}\n"))
+        .collect();
+
+    Compilation {
+        result: Ok(tiny_code),
+        warnings,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serial_test::serial;
+
+    #[test]
+    #[serial]
+    fn compile_reports_errors_by_kind() {
+        let program = r"
+            PROGRAM test
+            BEGIN
+                FUNCTION VOID main()
+                BEGIN
+                    INT a;
+                    a := 1 +;
+                    a := b;
+                END
+            END
+        ";
+
+        let errors = compile(program).result.unwrap_err();
+
+        assert!(matches!(
+            errors.as_slice(),
+            [
+                CompileError::Syntax(_),
+                CompileError::Symbol(SymbolError::UseUndeclaredSymbol(_), _)
+            ]
+        ));
+    }
+
+    #[test]
+    #[serial]
+    fn compile_reports_errors_after_a_symbol_error() {
+        let program = r"
+            PROGRAM test
+            BEGIN
+                FUNCTION VOID main()
+                BEGIN
+                    INT a;
+                    a := 1 +;
+                    a := zz;
+                    a := ;
+                    a := yy;
+                    a := a + 1;
+                END
+            END
+        ";
+
+        let errors = compile(program).result.unwrap_err();
+        let text = |error: &CompileError| {
+            let span = error.diagnostic().primary.span;
+            &program[span.start..span.end]
+        };
+
+        assert!(matches!(
+            errors.as_slice(),
+            [
+                CompileError::Syntax(_),
+                CompileError::Symbol(SymbolError::UseUndeclaredSymbol(_), _),
+                CompileError::Syntax(_),
+                CompileError::Symbol(SymbolError::UseUndeclaredSymbol(_), _)
+            ]
+        ));
+        assert_eq!("zz", text(&errors[1]));
+        assert_eq!("yy", text(&errors[3]));
+    }
+
+    #[test]
+    #[serial]
+    fn compile_reports_one_error_for_a_broken_if_condition() {
+        let program = r"
+            PROGRAM test
+            BEGIN
+                FUNCTION VOID main()
+                BEGIN
+                    INT x;
+                    IF (x < )
+                        x := 1;
+                    ELSE
+                        x := 2;
+                    FI
+                    x := x + 1;
+                    WRITE(x);
+                END
+            END
+        ";
+
+        let errors = compile(program).result.unwrap_err();
+
+        assert!(matches!(errors.as_slice(), [CompileError::Syntax(_)]));
+    }
+
+    #[test]
+    #[serial]
+    fn compile_can_be_called_more_than_once() {
+        let program = r"
+            PROGRAM test
+            BEGIN
+                INT a;
+
+                FUNCTION VOID main()
+                BEGIN
+                    a := 1;
+                    WRITE(a);
+                END
+            END
+        ";
+
+        let first = compile(program).result.unwrap();
+        let second = compile(program).result.unwrap();
+
+        assert!(!first.is_empty());
+        assert!(!second.is_empty());
+    }
+}
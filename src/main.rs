@@ -1,37 +1,9 @@
-#![feature(box_into_inner)]
-#![allow(unused_imports)]
-mod asm;
-mod ast;
-mod cfg;
-mod register_alloc;
-mod symbol_table;
-mod three_addr_code_ir;
-mod token;
-
-#[macro_use]
-extern crate maplit;
-
-#[macro_use]
-extern crate lalrpop_util;
-
-// use crate::asm::tiny::TinyCodeSequence;
-use crate::cfg::basic_block::BBFunction;
-use crate::symbol_table::{SymbolTable, SYMBOL_TABLE};
-use crate::three_addr_code_ir::three_address_code::{
-    visit::ThreeAddressCodeVisitor, ThreeAddressCode,
-};
-
-use crate::asm::tiny::{TinyCodeSequence, ALLOWED_REGISTERS};
-use crate::cfg::liveness::LivenessDecoratedControlFlowGraph;
-use crate::cfg::ControlFlowGraph;
 use flexi_logger::Logger;
+use microc::span::LineMap;
 use std::error::Error;
 use std::fs::File;
 use std::io;
 use std::io::{BufReader, ErrorKind, Read};
-use three_addr_code_ir::three_address_code::visit::CodeObject;
-
-lalrpop_mod!(pub microc);
 
 // TODO: Better error handling in general. Look here - https://doc.rust-lang.org/book/ch12-06-writing-to-stderr-instead-of-stdout.html
 fn main() {
@@ -61,7 +33,15 @@ fn main() {
         result_file.read_to_string(&mut result)?;
 
         println!("Beginning parsing file: [{}]", input_file_name);
-        let program = microc::ProgramParser::new().parse(&buf);
+        let compilation = microc::compile(&buf);
+
+        let line_map = LineMap::new(&buf);
+        let errors = compilation.result.as_ref().err().into_iter().flatten();
+        compilation
+            .warnings
+            .iter()
+            .chain(errors.map(|error| error.diagnostic()))
+            .for_each(|diagnostic| eprintln!("{}", diagnostic.render(input_file_name, &line_map)));
 
         /* STAGE 2 result verification */
         // let result = result.trim();
@@ -89,91 +69,24 @@ fn main() {
         /*******************************/
 
         /* STAGE 4,5,6 result verification */
-        let mut result = program.unwrap();
-        let mut visitor = ThreeAddressCodeVisitor;
-        result.reverse();
-        // let three_addr_codes: Vec<ThreeAddressCode> = result
-        //     .into_iter()
-        //     .flat_map(|ast_node| visitor.walk_ast(ast_node).code_sequence)
-        //     .collect();
-        //
-        // three_addr_codes
-        //     .clone()
-        //     .into_iter()
-        //     .for_each(|code| println!(";{}", code));
-
-        // result
-        //     .into_iter()
-        //     .map(|ast_node| visitor.walk_ast(ast_node))
-        //     .map(|code_object| Into::<BBFunction>::into(code_object))
-        //     .for_each(|x| println!("{x}"));
-
-        let num_functions = result.len();
-
-        result
-            .into_iter()
-            .enumerate()
-            .map(|(i, ast_node)| (i, visitor.walk_ast(ast_node)))
-            .map(|(i, code_object)| {
-                (
-                    i,
-                    ControlFlowGraph::from(Into::<BBFunction>::into(code_object)),
-                )
-            })
-            .map(|(i, cfg)| {
-                let cfg = LivenessDecoratedControlFlowGraph::from(cfg);
-                // println!("{cfg}");
-                (i, cfg)
-            })
-            .map(|(i, cfg)| {
-                (
-                    i,
-                    register_alloc::perform_register_allocation(cfg, ALLOWED_REGISTERS),
+        match compilation.result {
+            Ok(tiny_code) => print!("{}", tiny_code),
+            Err(errors) => {
+                return Err(format!(
+                    "aborting due to {} previous error{}",
+                    errors.len(),
+                    if errors.len() == 1 { "" } else { "s" }
                 )
-            })
-            .map(|(i, (register_alloc_tacs, stack_space_consumed))| {
-                if i == 0 {
-                    Into::<TinyCodeSequence>::into((
-                        register_alloc_tacs,
-                        stack_space_consumed,
-                        true,
-                        false,
-                    ))
-                } else if i == num_functions - 1 {
-                    Into::<TinyCodeSequence>::into((
-                        register_alloc_tacs,
-                        stack_space_consumed,
-                        false,
-                        true,
-                    ))
-                } else {
-                    Into::<TinyCodeSequence>::into((
-                        register_alloc_tacs,
-                        stack_space_consumed,
-                        false,
-                        false,
-                    ))
-                }
-            })
-            .for_each(|tiny_code_seq| {
-                tiny_code_seq
-                    .sequence
-                    .into_iter()
-                    .for_each(|code| println!("{code}"))
-            });
-
-        // let tiny_code: TinyCodeSequence = three_addr_codes.into();
-        // tiny_code
-        //     .sequence
-        //     .into_iter()
-        //     .for_each(|code| println!("{code}"));
+                .into())
+            }
+        }
         /*******************************/
 
         Ok(())
     }
 
     if let Err(e) = inner_main() {
-        eprintln!("Unable to complete parsing input: {}", e);
+        eprintln!("error: {}", e);
         std::process::exit(1);
     }
 }
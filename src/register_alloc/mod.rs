@@ -1,13 +1,7 @@
-use crate::cfg::basic_block::BBLabel;
-use crate::cfg::liveness::{
-    LivenessDecoratedControlFlowGraph, LivenessDecoratedThreeAddressCode, LivenessMetadata,
-};
-use crate::register_alloc::types::{
-    RegisterAllocatedThreeAddressCode, RegisterFile, RegisterId, Spill,
-};
+use crate::cfg::liveness::{LivenessDecoratedControlFlowGraph, LivenessMetadata};
+use crate::register_alloc::types::{RegisterAllocatedThreeAddressCode, RegisterFile, Spill};
 use crate::three_addr_code_ir::three_address_code::ThreeAddressCode;
 use crate::three_addr_code_ir::{RValueF, RValueI};
-use std::collections::HashSet;
 
 pub mod types;
 
@@ -43,29 +37,14 @@ pub fn perform_register_allocation(
                 let (tac, liveness_metadata) = liveness_decorated_tac.into_parts();
 
                 let mut reg_alloc_tac = match &tac {
-                    ThreeAddressCode::AddI {
-                        lhs,
-                        rhs,
-                        temp_result,
-                    }
-                    | ThreeAddressCode::SubI {
-                        lhs,
-                        rhs,
-                        temp_result,
-                    }
-                    | ThreeAddressCode::MulI {
-                        lhs,
-                        rhs,
-                        temp_result,
-                    }
-                    | ThreeAddressCode::DivI {
-                        lhs,
-                        rhs,
-                        temp_result,
-                    } => {
+                    ThreeAddressCode::AddI { lhs, rhs, result }
+                    | ThreeAddressCode::SubI { lhs, rhs, result }
+                    | ThreeAddressCode::MulI { lhs, rhs, result }
+                    | ThreeAddressCode::DivI { lhs, rhs, result }
+                    | ThreeAddressCode::ModI { lhs, rhs, result } => {
                         let lhs_lvalue = lhs.to_lvalue();
                         let rhs_lvalue = rhs.to_lvalue();
-                        let result = temp_result.to_lvalue();
+                        let result = result.to_lvalue();
 
                         // Ensure operands have a register
                         let lhs_reg_alloc = register_file.ensure_register(
@@ -79,9 +58,14 @@ pub fn perform_register_allocation(
                             true,
                         );
 
-                        // Ensure the result has a register
-                        let result_reg_alloc =
-                            register_file.allocate_register(result.clone(), &liveness_metadata);
+                        // Ensure the result has a register. The result may be
+                        // a variable that already has one, when the op writes
+                        // back into one of its operands.
+                        let result_reg_alloc = register_file.ensure_register(
+                            result.clone(),
+                            &liveness_metadata,
+                            false,
+                        );
 
                         // Free operand register if operands are no longer live
                         if !liveness_metadata.is_var_live(&lhs_lvalue) {
@@ -111,29 +95,13 @@ pub fn perform_register_allocation(
 
                         reg_alloc_tac
                     }
-                    ThreeAddressCode::AddF {
-                        lhs,
-                        rhs,
-                        temp_result,
-                    }
-                    | ThreeAddressCode::SubF {
-                        lhs,
-                        rhs,
-                        temp_result,
-                    }
-                    | ThreeAddressCode::MulF {
-                        lhs,
-                        rhs,
-                        temp_result,
-                    }
-                    | ThreeAddressCode::DivF {
-                        lhs,
-                        rhs,
-                        temp_result,
-                    } => {
+                    ThreeAddressCode::AddF { lhs, rhs, result }
+                    | ThreeAddressCode::SubF { lhs, rhs, result }
+                    | ThreeAddressCode::MulF { lhs, rhs, result }
+                    | ThreeAddressCode::DivF { lhs, rhs, result } => {
                         let lhs_lvalue = lhs.to_lvalue();
                         let rhs_lvalue = rhs.to_lvalue();
-                        let result = temp_result.to_lvalue();
+                        let result = result.to_lvalue();
 
                         // Ensure operands have a register
                         let lhs_reg_alloc = register_file.ensure_register(
@@ -147,9 +115,14 @@ pub fn perform_register_allocation(
                             true,
                         );
 
-                        // Ensure the result has a register
-                        let result_reg_alloc =
-                            register_file.allocate_register(result.clone(), &liveness_metadata);
+                        // Ensure the result has a register. The result may be
+                        // a variable that already has one, when the op writes
+                        // back into one of its operands.
+                        let result_reg_alloc = register_file.ensure_register(
+                            result.clone(),
+                            &liveness_metadata,
+                            false,
+                        );
 
                         // Free operand register if operands are no longer live
                         if !liveness_metadata.is_var_live(&lhs_lvalue) {
@@ -311,10 +284,19 @@ pub fn perform_register_allocation(
 
                         reg_alloc_tac
                     }
-                    ThreeAddressCode::ReadI { identifier }
-                    | ThreeAddressCode::WriteI { identifier } => {
+                    ThreeAddressCode::ReadI { .. }
+                    | ThreeAddressCode::WriteI { .. }
+                    | ThreeAddressCode::ReadF { .. }
+                    | ThreeAddressCode::WriteF { .. } => {
+                        let ident_lvalue = match &tac {
+                            ThreeAddressCode::ReadI { identifier } => identifier.to_lvalue(),
+                            ThreeAddressCode::WriteI { value } => value.to_lvalue(),
+                            ThreeAddressCode::ReadF { identifier } => identifier.to_lvalue(),
+                            ThreeAddressCode::WriteF { value } => value.to_lvalue(),
+                            _ => unreachable!(),
+                        };
+
                         // Ensure operands have a register
-                        let ident_lvalue = identifier.to_lvalue();
                         let ident_reg_alloc = register_file.ensure_register(
                             ident_lvalue.clone(),
                             &liveness_metadata,
@@ -336,28 +318,237 @@ pub fn perform_register_allocation(
 
                         reg_alloc_tac
                     }
-                    ThreeAddressCode::ReadF { identifier }
-                    | ThreeAddressCode::WriteF { identifier } => {
-                        // Ensure operands have a register
-                        let ident_lvalue = identifier.to_lvalue();
-                        let ident_reg_alloc = register_file.ensure_register(
-                            ident_lvalue.clone(),
+                    ThreeAddressCode::LoadElementI {
+                        index, temp_result, ..
+                    } => {
+                        let index_lvalue = index.to_lvalue();
+                        let result = temp_result.to_lvalue();
+
+                        // Ensure the index has a register
+                        let index_reg_alloc = register_file.ensure_register(
+                            index_lvalue.clone(),
+                            &liveness_metadata,
+                            true,
+                        );
+
+                        // Ensure the result has a register
+                        let result_reg_alloc =
+                            register_file.allocate_register(result.clone(), &liveness_metadata);
+
+                        // Free index register if the index is no longer live
+                        if !liveness_metadata.is_var_live(&index_lvalue) {
+                            let _ = register_file
+                                .free_register(index_reg_alloc.register_id(), &liveness_metadata);
+                        }
+
+                        let mut reg_alloc_tac = RegisterAllocatedThreeAddressCode::new(tac);
+
+                        register_file.set_register_dirty(result_reg_alloc.register_id());
+
+                        reg_alloc_tac
+                            .add_register_allocation(index_lvalue, index_reg_alloc.register_id());
+                        reg_alloc_tac
+                            .add_register_allocation(result, result_reg_alloc.register_id());
+
+                        reg_alloc_tac.add_spills(index_reg_alloc.to_spills());
+                        reg_alloc_tac.add_spills(result_reg_alloc.to_spills());
+
+                        reg_alloc_tac
+                    }
+                    ThreeAddressCode::StoreElementI { index, value, .. } => {
+                        let index_lvalue = index.to_lvalue();
+                        let value_lvalue = value.to_lvalue();
+
+                        // Ensure operands have a register. The element itself
+                        // is written straight to memory and needs no register.
+                        let index_reg_alloc = register_file.ensure_register(
+                            index_lvalue.clone(),
+                            &liveness_metadata,
+                            true,
+                        );
+                        let value_reg_alloc = register_file.ensure_register(
+                            value_lvalue.clone(),
                             &liveness_metadata,
                             true,
                         );
 
                         // Free operand register if operands are no longer live
-                        if !liveness_metadata.is_var_live(&ident_lvalue) {
+                        if !liveness_metadata.is_var_live(&index_lvalue) {
                             let _ = register_file
-                                .free_register(ident_reg_alloc.register_id(), &liveness_metadata);
-                        } else if tac.is_read() {
-                            register_file.set_register_dirty(ident_reg_alloc.register_id());
+                                .free_register(index_reg_alloc.register_id(), &liveness_metadata);
+                        }
+
+                        if !liveness_metadata.is_var_live(&value_lvalue) {
+                            let _ = register_file
+                                .free_register(value_reg_alloc.register_id(), &liveness_metadata);
                         }
 
                         let mut reg_alloc_tac = RegisterAllocatedThreeAddressCode::new(tac);
+
                         reg_alloc_tac
-                            .add_register_allocation(ident_lvalue, ident_reg_alloc.register_id());
-                        reg_alloc_tac.add_spills(ident_reg_alloc.to_spills());
+                            .add_register_allocation(index_lvalue, index_reg_alloc.register_id());
+                        reg_alloc_tac
+                            .add_register_allocation(value_lvalue, value_reg_alloc.register_id());
+
+                        reg_alloc_tac.add_spills(index_reg_alloc.to_spills());
+                        reg_alloc_tac.add_spills(value_reg_alloc.to_spills());
+
+                        reg_alloc_tac
+                    }
+                    ThreeAddressCode::LoadElementF {
+                        index, temp_result, ..
+                    } => {
+                        let index_lvalue = index.to_lvalue();
+                        let result = temp_result.to_lvalue();
+
+                        // Ensure the index has a register
+                        let index_reg_alloc = register_file.ensure_register(
+                            index_lvalue.clone(),
+                            &liveness_metadata,
+                            true,
+                        );
+
+                        // Ensure the result has a register
+                        let result_reg_alloc =
+                            register_file.allocate_register(result.clone(), &liveness_metadata);
+
+                        // Free index register if the index is no longer live
+                        if !liveness_metadata.is_var_live(&index_lvalue) {
+                            let _ = register_file
+                                .free_register(index_reg_alloc.register_id(), &liveness_metadata);
+                        }
+
+                        let mut reg_alloc_tac = RegisterAllocatedThreeAddressCode::new(tac);
+
+                        register_file.set_register_dirty(result_reg_alloc.register_id());
+
+                        reg_alloc_tac
+                            .add_register_allocation(index_lvalue, index_reg_alloc.register_id());
+                        reg_alloc_tac
+                            .add_register_allocation(result, result_reg_alloc.register_id());
+
+                        reg_alloc_tac.add_spills(index_reg_alloc.to_spills());
+                        reg_alloc_tac.add_spills(result_reg_alloc.to_spills());
+
+                        reg_alloc_tac
+                    }
+                    ThreeAddressCode::StoreElementF { index, value, .. } => {
+                        let index_lvalue = index.to_lvalue();
+                        let value_lvalue = value.to_lvalue();
+
+                        // Ensure operands have a register. The element itself
+                        // is written straight to memory and needs no register.
+                        let index_reg_alloc = register_file.ensure_register(
+                            index_lvalue.clone(),
+                            &liveness_metadata,
+                            true,
+                        );
+                        let value_reg_alloc = register_file.ensure_register(
+                            value_lvalue.clone(),
+                            &liveness_metadata,
+                            true,
+                        );
+
+                        // Free operand register if operands are no longer live
+                        if !liveness_metadata.is_var_live(&index_lvalue) {
+                            let _ = register_file
+                                .free_register(index_reg_alloc.register_id(), &liveness_metadata);
+                        }
+
+                        if !liveness_metadata.is_var_live(&value_lvalue) {
+                            let _ = register_file
+                                .free_register(value_reg_alloc.register_id(), &liveness_metadata);
+                        }
+
+                        let mut reg_alloc_tac = RegisterAllocatedThreeAddressCode::new(tac);
+
+                        reg_alloc_tac
+                            .add_register_allocation(index_lvalue, index_reg_alloc.register_id());
+                        reg_alloc_tac
+                            .add_register_allocation(value_lvalue, value_reg_alloc.register_id());
+
+                        reg_alloc_tac.add_spills(index_reg_alloc.to_spills());
+                        reg_alloc_tac.add_spills(value_reg_alloc.to_spills());
+
+                        reg_alloc_tac
+                    }
+                    ThreeAddressCode::IntToFloat {
+                        operand,
+                        temp_result,
+                    } => {
+                        let operand_lvalue = operand.to_lvalue();
+                        let result = temp_result.to_lvalue();
+
+                        // Ensure the operand has a register
+                        let operand_reg_alloc = register_file.ensure_register(
+                            operand_lvalue.clone(),
+                            &liveness_metadata,
+                            true,
+                        );
+
+                        // Ensure the result has a register
+                        let result_reg_alloc =
+                            register_file.allocate_register(result.clone(), &liveness_metadata);
+
+                        // Free operand register if the operand is no longer live
+                        if !liveness_metadata.is_var_live(&operand_lvalue) {
+                            let _ = register_file
+                                .free_register(operand_reg_alloc.register_id(), &liveness_metadata);
+                        }
+
+                        let mut reg_alloc_tac = RegisterAllocatedThreeAddressCode::new(tac);
+
+                        register_file.set_register_dirty(result_reg_alloc.register_id());
+
+                        reg_alloc_tac.add_register_allocation(
+                            operand_lvalue,
+                            operand_reg_alloc.register_id(),
+                        );
+                        reg_alloc_tac
+                            .add_register_allocation(result, result_reg_alloc.register_id());
+
+                        reg_alloc_tac.add_spills(operand_reg_alloc.to_spills());
+                        reg_alloc_tac.add_spills(result_reg_alloc.to_spills());
+
+                        reg_alloc_tac
+                    }
+                    ThreeAddressCode::FloatToInt {
+                        operand,
+                        temp_result,
+                    } => {
+                        let operand_lvalue = operand.to_lvalue();
+                        let result = temp_result.to_lvalue();
+
+                        // Ensure the operand has a register
+                        let operand_reg_alloc = register_file.ensure_register(
+                            operand_lvalue.clone(),
+                            &liveness_metadata,
+                            true,
+                        );
+
+                        // Ensure the result has a register
+                        let result_reg_alloc =
+                            register_file.allocate_register(result.clone(), &liveness_metadata);
+
+                        // Free operand register if the operand is no longer live
+                        if !liveness_metadata.is_var_live(&operand_lvalue) {
+                            let _ = register_file
+                                .free_register(operand_reg_alloc.register_id(), &liveness_metadata);
+                        }
+
+                        let mut reg_alloc_tac = RegisterAllocatedThreeAddressCode::new(tac);
+
+                        register_file.set_register_dirty(result_reg_alloc.register_id());
+
+                        reg_alloc_tac.add_register_allocation(
+                            operand_lvalue,
+                            operand_reg_alloc.register_id(),
+                        );
+                        reg_alloc_tac
+                            .add_register_allocation(result, result_reg_alloc.register_id());
+
+                        reg_alloc_tac.add_spills(operand_reg_alloc.to_spills());
+                        reg_alloc_tac.add_spills(result_reg_alloc.to_spills());
 
                         reg_alloc_tac
                     }
@@ -525,12 +716,11 @@ pub fn perform_register_allocation(
 
 #[cfg(test)]
 mod test {
-    use crate::cfg::liveness::{LivenessDecoratedThreeAddressCode, LivenessMetadata};
-    use crate::register_alloc::types::RegisterAllocatedThreeAddressCode;
+    use crate::cfg::liveness::LivenessMetadata;
+    use crate::register_alloc::types::{RegisterFile, SpillType};
     use crate::symbol_table::symbol::data;
-    use crate::three_addr_code_ir::three_address_code::ThreeAddressCode;
-    use crate::three_addr_code_ir::LValueI;
-    use crate::three_addr_code_ir::{IdentI, RValueI, TempI};
+    use crate::three_addr_code_ir::IdentI;
+    use crate::three_addr_code_ir::{LValue, LValueI};
     use std::rc::Rc;
 
     // #[test]
@@ -929,4 +1119,38 @@ mod test {
     // }
 
     // TODO: Modify tests to ensure correct end of bb spills are generated
+
+    #[test]
+    fn call_stores_back_dirty_global_dead_after_it() {
+        let g: LValue = LValueI::Id(IdentI(data::Symbol::NonFunctionScopedSymbol(Rc::new(
+            data::NonFunctionScopedSymbol::Int {
+                name: "g".to_owned(),
+            },
+        ))))
+        .into();
+
+        // STOREI 1 g, leaving g dirty in a register
+        let mut register_file = RegisterFile::new(4);
+        let store_liveness =
+            LivenessMetadata::new(hashset! {}, hashset! {g.clone()}, hashset! {}, hashset! {});
+        let register_id = register_file
+            .ensure_register(g.clone(), &store_liveness, false)
+            .register_id();
+        register_file.set_register_dirty(register_id);
+
+        // JSR, which uses g, that is overwritten after the call
+        let jsr_liveness = LivenessMetadata::new(
+            hashset! {g.clone()},
+            hashset! {},
+            hashset! {g.clone()},
+            hashset! {},
+        );
+        let spills = register_file.free_registers_with_global_vars(&jsr_liveness);
+
+        assert_eq!(spills.len(), 1);
+        assert!(matches!(
+            spills[0].clone().into_parts(),
+            (SpillType::Store, id, data::Symbol::NonFunctionScopedSymbol(_)) if id == register_id
+        ));
+    }
 }
@@ -3,7 +3,7 @@ use crate::symbol_table::symbol::data;
 use crate::symbol_table::symbol::data::{FunctionScopedSymbol, FunctionScopedSymbolType};
 use crate::three_addr_code_ir::three_address_code::ThreeAddressCode;
 use crate::three_addr_code_ir::{IdentF, IdentI, LValue, LValueF, LValueI, TempF, TempI};
-use std::collections::{HashMap, HashSet};
+use std::collections::HashMap;
 use std::ops::{Index, IndexMut};
 use std::rc::Rc;
 
@@ -258,6 +258,12 @@ impl RegisterFile {
         let _ = self.tempf_to_func_scoped_symbol_map.insert(temp, symbol);
     }
 
+    /// Frees the registers holding global variables ahead of a
+    /// call, `liveness` being that of the `Jsr`. The callee may
+    /// use any global, so a dirty global is stored back if it is
+    /// used by the call, even if it is dead after the call - like
+    /// a global passed as a VAR argument, which is only written
+    /// to once the call returns.
     pub fn free_registers_with_global_vars(&mut self, liveness: &LivenessMetadata) -> Vec<Spill> {
         let mut result = vec![];
 
@@ -269,7 +275,10 @@ impl RegisterFile {
             .collect();
 
         regs_with_global_vars_ids.into_iter().for_each(|id| {
-            if let Some(spill) = self.free_register(id, liveness) {
+            let is_used = self[id]
+                .value()
+                .is_some_and(|value| liveness.is_var_used(value));
+            if let Some(spill) = self.release_register(id, is_used) {
                 result.push(spill);
             }
         });
@@ -436,15 +445,25 @@ impl RegisterFile {
         register_id: RegisterId,
         liveness: &LivenessMetadata,
     ) -> Option<Spill> {
+        let is_live = self[register_id]
+            .value()
+            .is_some_and(|value| liveness.is_var_live(value));
+
+        self.release_register(register_id, is_live)
+    }
+
+    /// Frees the register, generating a `Store` type register
+    /// spill if it is dirty and its value is `needed`.
+    fn release_register(&mut self, register_id: RegisterId, needed: bool) -> Option<Spill> {
         let register = &mut self[register_id];
 
         let curr_val = register.remove_value();
 
         // If register is dirty and its current value
-        // is live, a `Store` type register spill must
+        // is needed, a `Store` type register spill must
         // be generated.
         if let Some(value) = curr_val {
-            if register.is_dirty() && liveness.is_var_live(&value) {
+            if register.is_dirty() && needed {
                 // Mark register as free
                 register.set_free();
 
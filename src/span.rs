@@ -0,0 +1,108 @@
+/// A range of byte offsets into the source
+/// of a program, from `start` up to but
+/// not including `end`.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Self {
+        Self { start, end }
+    }
+
+    /// Returns the smallest span that
+    /// covers both `self` and `other`.
+    pub fn to(self, other: Span) -> Span {
+        Span::new(self.start.min(other.start), self.end.max(other.end))
+    }
+}
+
+/// A position in the source of a program. Both
+/// the line and the column are numbered from 1,
+/// and the column counts characters, not bytes.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, derive_more::Display)]
+#[display(fmt = "{}:{}", line, col)]
+pub struct LineCol {
+    pub line: usize,
+    pub col: usize,
+}
+
+/// Maps byte offsets into the source
+/// of a program to lines and columns.
+pub struct LineMap<'input> {
+    source: &'input str,
+    // Byte offset of the start of each line
+    line_starts: Vec<usize>,
+}
+
+impl<'input> LineMap<'input> {
+    pub fn new(source: &'input str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(source.match_indices('\n').map(|(offset, _)| offset + 1))
+            .collect();
+
+        Self {
+            source,
+            line_starts,
+        }
+    }
+
+    /// Returns the line and column of the character at `offset`.
+    /// An offset past the end of the source maps to the position
+    /// just after its last character.
+    pub fn line_col(&self, offset: usize) -> LineCol {
+        let offset = offset.min(self.source.len());
+        let line = self
+            .line_starts
+            .partition_point(|&line_start| line_start <= offset);
+        let line_start = self.line_starts[line - 1];
+
+        LineCol {
+            line,
+            col: self.source[line_start..offset].chars().count() + 1,
+        }
+    }
+
+    /// Returns the text of the line numbered
+    /// `line`, without its line terminator.
+    pub fn line(&self, line: usize) -> &'input str {
+        let start = self.line_starts[line - 1];
+        let end = self
+            .line_starts
+            .get(line)
+            .copied()
+            .unwrap_or(self.source.len());
+
+        self.source[start..end].trim_end_matches(&['\n', '\r'][..])
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn offsets_map_to_one_based_lines_and_columns() {
+        let line_map = LineMap::new("PROGRAM p\nBEGIN\n\nEND");
+
+        assert_eq!(LineCol { line: 1, col: 1 }, line_map.line_col(0));
+        assert_eq!(LineCol { line: 1, col: 9 }, line_map.line_col(8));
+        // The newline ends the line it is on
+        assert_eq!(LineCol { line: 1, col: 10 }, line_map.line_col(9));
+        assert_eq!(LineCol { line: 2, col: 1 }, line_map.line_col(10));
+        assert_eq!(LineCol { line: 3, col: 1 }, line_map.line_col(16));
+        assert_eq!(LineCol { line: 4, col: 3 }, line_map.line_col(19));
+        assert_eq!(LineCol { line: 4, col: 4 }, line_map.line_col(100));
+    }
+
+    #[test]
+    fn columns_count_characters_rather_than_bytes() {
+        let line_map = LineMap::new("-- π ≈ 3.14\nx := 1;");
+
+        assert_eq!(LineCol { line: 1, col: 7 }, line_map.line_col(9));
+        assert_eq!(LineCol { line: 2, col: 1 }, line_map.line_col(15));
+        assert_eq!(LineCol { line: 2, col: 7 }, line_map.line_col(21));
+    }
+}
@@ -1,3 +1,5 @@
+use crate::diagnostic::{Code, Diagnostic};
+use crate::span::Span;
 use getset::Getters;
 
 /// Type to represent errors originating
@@ -74,6 +76,60 @@ impl DeclareInInvalidScopeError {
     }
 }
 
+/// Type to represent errors originating
+/// from using the result of a call to a
+/// function that does not return a value.
+#[derive(Debug, derive_more::Error, derive_more::Display, Getters, Eq, PartialEq, Clone)]
+#[display(
+    fmt = "Function [{}] returns VOID and its result cannot be used as a value.",
+    function_name
+)]
+#[getset(get = "pub")]
+pub struct UseVoidFunctionResultError {
+    function_name: String,
+}
+
+impl UseVoidFunctionResultError {
+    pub fn new(function_name: String) -> Self {
+        UseVoidFunctionResultError { function_name }
+    }
+}
+
+/// Type to represent errors originating from
+/// defining a function differently from its
+/// prototype.
+#[derive(Debug, derive_more::Error, derive_more::Display, Getters, Eq, PartialEq, Clone)]
+#[display(
+    fmt = "Definition of function [{}] does not match its prototype.",
+    function_name
+)]
+#[getset(get = "pub")]
+pub struct MismatchedFunctionPrototypeError {
+    function_name: String,
+}
+
+impl MismatchedFunctionPrototypeError {
+    pub fn new(function_name: String) -> Self {
+        MismatchedFunctionPrototypeError { function_name }
+    }
+}
+
+/// Type to represent errors originating from
+/// calling a function that is declared by a
+/// prototype, but never defined.
+#[derive(Debug, derive_more::Error, derive_more::Display, Getters, Eq, PartialEq, Clone)]
+#[display(fmt = "Function [{}] is called but never defined.", function_name)]
+#[getset(get = "pub")]
+pub struct UseUndefinedFunctionError {
+    function_name: String,
+}
+
+impl UseUndefinedFunctionError {
+    pub fn new(function_name: String) -> Self {
+        UseUndefinedFunctionError { function_name }
+    }
+}
+
 /// Type representing possible errors
 /// that can happen while using symbols
 #[derive(Debug, derive_more::Error, derive_more::Display, PartialEq, Eq, Clone)]
@@ -87,4 +143,76 @@ pub enum SymbolError {
     /// scope. For instance, functions are the only
     /// scope where parameter symbols can be declared.
     DeclareInInvalidSymbolScope(DeclareInInvalidScopeError),
+    /// User tries to use the result of a call
+    /// to a VOID function in an expression.
+    UseVoidFunctionResult(UseVoidFunctionResultError),
+    /// User defines a function with a different
+    /// signature than its prototype.
+    MismatchedFunctionPrototype(MismatchedFunctionPrototypeError),
+    /// User calls a function that is declared
+    /// by a prototype, but never defined.
+    UseUndefinedFunction(UseUndefinedFunctionError),
+}
+
+impl SymbolError {
+    /// Returns the diagnostic that reports the error
+    /// for the source at `span`, where it was found.
+    pub fn to_diagnostic(&self, span: Span) -> Diagnostic {
+        match self {
+            SymbolError::DeclareExistingSymbol(err) => Diagnostic::error(
+                Code::DuplicateDeclaration,
+                format!(
+                    "`{}` is declared more than once in scope `{}`",
+                    err.symbol_name(),
+                    err.scope_name()
+                ),
+                span,
+            )
+            .with_label("declared again here"),
+            SymbolError::UseUndeclaredSymbol(err) => Diagnostic::error(
+                Code::UndeclaredSymbol,
+                format!("use of undeclared symbol `{}`", err.symbol_name()),
+                span,
+            )
+            .with_label("not declared in this scope"),
+            SymbolError::DeclareInInvalidSymbolScope(err) => Diagnostic::error(
+                Code::InvalidScope,
+                format!(
+                    "`{}` cannot be declared in {} scope `{}`",
+                    err.symbol_name(),
+                    err.scope_type().to_string().to_lowercase(),
+                    err.scope_name()
+                ),
+                span,
+            )
+            .with_label("declared here"),
+            SymbolError::UseVoidFunctionResult(err) => Diagnostic::error(
+                Code::VoidValue,
+                format!(
+                    "function `{}` returns VOID, so its result cannot be used as a value",
+                    err.function_name()
+                ),
+                span,
+            )
+            .with_label("used as a value here"),
+            SymbolError::MismatchedFunctionPrototype(err) => Diagnostic::error(
+                Code::PrototypeMismatch,
+                format!(
+                    "definition of function `{}` does not match its prototype",
+                    err.function_name()
+                ),
+                span,
+            )
+            .with_label("defined here"),
+            SymbolError::UseUndefinedFunction(err) => Diagnostic::error(
+                Code::UndefinedFunction,
+                format!(
+                    "function `{}` is called but never defined",
+                    err.function_name()
+                ),
+                span,
+            )
+            .with_label("called here"),
+        }
+    }
 }
@@ -8,14 +8,19 @@ pub mod symbol;
 #[cfg(test)]
 pub use test::setup as symbol_table_test_setup;
 
+use crate::diagnostic::Diagnostic;
+use crate::error::CompileError;
+use crate::span::Span;
 use crate::symbol_table::error::{
-    DeclareExistingSymbolError, SymbolError, UseUndeclaredSymbolError,
+    DeclareExistingSymbolError, MismatchedFunctionPrototypeError, SymbolError,
+    UseUndeclaredSymbolError,
 };
 use crate::symbol_table::scope::Scope;
 use crate::symbol_table::scope_tree::ScopeTree;
 use crate::symbol_table::symbol::data;
 use crate::symbol_table::symbol::function;
 use crate::symbol_table::symbol::NumType;
+use linked_hash_map::LinkedHashMap;
 use linked_hash_set::LinkedHashSet;
 use std::cell::RefCell;
 use std::collections::{HashMap, HashSet};
@@ -26,10 +31,7 @@ use std::sync::atomic::{AtomicI32, AtomicU32, Ordering};
 thread_local! {
     /// Thread local, global symbol table that
     /// assumes the compiler to be single threaded.
-    pub static SYMBOL_TABLE: RefCell<SymbolTable> = RefCell::new(SymbolTable {
-        scope_tree: ScopeTree::new(),
-        symbol_errors: vec![],
-    });
+    pub static SYMBOL_TABLE: RefCell<SymbolTable> = RefCell::new(SymbolTable::new());
 }
 
 static ANONYMOUS_SCOPE_COUNTER: AtomicU32 = AtomicU32::new(1);
@@ -41,10 +43,63 @@ static ANONYMOUS_SCOPE_COUNTER: AtomicU32 = AtomicU32::new(1);
 #[derive(Debug)]
 pub struct SymbolTable {
     scope_tree: ScopeTree,
-    symbol_errors: Vec<SymbolError>,
+    // Symbol and semantic errors the parser
+    // continued past.
+    symbol_errors: Vec<CompileError>,
+    // Syntax errors the parser recovered from.
+    syntax_errors: Vec<CompileError>,
+    // Problems that do not stop the
+    // program from being compiled.
+    warnings: Vec<Diagnostic>,
+    // Number of loop scopes in the
+    // active scope stack.
+    loop_depth: usize,
+    // Anonymous STRING symbols for the
+    // string literals used in the program.
+    string_literals: Vec<Rc<data::NonFunctionScopedSymbol>>,
+    // Initial values of global variables,
+    // evaluated at compile time.
+    global_initializers: Vec<(Rc<data::NonFunctionScopedSymbol>, data::NumValue)>,
+    // First free local stack slot on entry to
+    // each of the active anonymous scopes.
+    block_slot_marks: Vec<usize>,
+    // Types of the local stack slots taken up
+    // by the function being parsed.
+    local_slots: Vec<NumType>,
+    // Functions declared by a prototype that have
+    // not been defined yet, along with where they
+    // are first called, if they have been called.
+    undefined_functions: LinkedHashMap<String, Option<Span>>,
+    // RECORD types declared in the program,
+    // which have a namespace of their own.
+    record_types: HashMap<String, Rc<data::RecordType>>,
 }
 
 impl SymbolTable {
+    fn new() -> Self {
+        SymbolTable {
+            scope_tree: ScopeTree::new(),
+            symbol_errors: vec![],
+            syntax_errors: vec![],
+            warnings: vec![],
+            loop_depth: 0,
+            string_literals: vec![],
+            global_initializers: vec![],
+            block_slot_marks: vec![],
+            local_slots: vec![],
+            undefined_functions: LinkedHashMap::new(),
+            record_types: HashMap::new(),
+        }
+    }
+
+    /// Empties the symbol table, so that
+    /// another program can be parsed.
+    pub fn reset() {
+        SYMBOL_TABLE.with(|symbol_table| {
+            *symbol_table.borrow_mut() = SymbolTable::new();
+        })
+    }
+
     pub fn global_symbols() -> Vec<Rc<data::NonFunctionScopedSymbol>> {
         SYMBOL_TABLE.with(|symbol_table| {
             let scope_tree = &symbol_table.borrow().scope_tree;
@@ -52,23 +107,151 @@ impl SymbolTable {
         })
     }
 
-    pub fn add_symbol_error(error: SymbolError) {
+    pub fn string_literals() -> Vec<Rc<data::NonFunctionScopedSymbol>> {
+        SYMBOL_TABLE.with(|symbol_table| symbol_table.borrow().string_literals.clone())
+    }
+
+    /// Returns the anonymous STRING symbol holding
+    /// `value`, a string literal including its quotes.
+    /// Identical literals share the same symbol. The
+    /// generated names contain an underscore, so they
+    /// can never clash with a Microc identifier.
+    pub fn intern_string_literal(value: &str) -> data::Symbol {
+        SYMBOL_TABLE.with(|symbol_table| {
+            let string_literals = &mut symbol_table.borrow_mut().string_literals;
+
+            let existing = string_literals.iter().find(|symbol| {
+                matches!(&***symbol, data::NonFunctionScopedSymbol::String { value: existing, .. } if existing == value)
+            });
+            let symbol = match existing {
+                Some(symbol) => Rc::clone(symbol),
+                None => {
+                    let symbol = Rc::new(data::NonFunctionScopedSymbol::String {
+                        name: format!("string_literal_{}", string_literals.len()),
+                        value: value.to_owned(),
+                    });
+                    string_literals.push(Rc::clone(&symbol));
+                    symbol
+                }
+            };
+
+            data::Symbol::NonFunctionScopedSymbol(symbol)
+        })
+    }
+
+    pub fn add_global_initializer(
+        symbol: Rc<data::NonFunctionScopedSymbol>,
+        value: data::NumValue,
+    ) {
+        SYMBOL_TABLE.with(|symbol_table| {
+            symbol_table
+                .borrow_mut()
+                .global_initializers
+                .push((symbol, value));
+        })
+    }
+
+    pub fn global_initializers() -> Vec<(Rc<data::NonFunctionScopedSymbol>, data::NumValue)> {
+        SYMBOL_TABLE.with(|symbol_table| symbol_table.borrow().global_initializers.clone())
+    }
+
+    /// Returns true if an array has been
+    /// declared in any scope of the program.
+    pub fn has_array_symbols() -> bool {
+        SYMBOL_TABLE.with(|symbol_table| {
+            symbol_table
+                .borrow()
+                .scope_tree
+                .scopes()
+                .any(|scope| scope.borrow().has_array_symbols())
+        })
+    }
+
+    /// Records an error in the use of a symbol, or any other
+    /// semantic error, that the parser continues past.
+    pub fn add_symbol_error(error: CompileError) {
         SYMBOL_TABLE.with(|symbol_table| {
             symbol_table.borrow_mut().symbol_errors.push(error);
         })
     }
 
+    /// Returns the symbol and semantic errors the parser
+    /// continued past, in the order they were found in.
+    pub fn symbol_errors() -> Vec<CompileError> {
+        SYMBOL_TABLE.with(|symbol_table| symbol_table.borrow().symbol_errors.clone())
+    }
+
+    pub fn add_syntax_error(error: CompileError) {
+        SYMBOL_TABLE.with(|symbol_table| {
+            symbol_table.borrow_mut().syntax_errors.push(error);
+        })
+    }
+
+    /// Returns the syntax errors the parser recovered from,
+    /// in the order they appear in the program.
+    pub fn syntax_errors() -> Vec<CompileError> {
+        SYMBOL_TABLE.with(|symbol_table| symbol_table.borrow().syntax_errors.clone())
+    }
+
     pub fn add_anonymous_scope() {
         SYMBOL_TABLE.with(|symbol_table| {
-            let scope_tree = &mut symbol_table.borrow_mut().scope_tree;
-            let active_scope = scope_tree.active_scope();
+            let mut symbol_table = symbol_table.borrow_mut();
+            let active_scope = symbol_table.scope_tree.active_scope();
 
             let anonymous_scope_name = format!(
                 "BLOCK{}",
                 ANONYMOUS_SCOPE_COUNTER.fetch_add(1, Ordering::SeqCst)
             );
             let new_scope = Scope::new_anonymous(anonymous_scope_name, active_scope);
-            scope_tree.add_new_scope(new_scope);
+            symbol_table.scope_tree.add_new_scope(new_scope);
+
+            symbol_table
+                .block_slot_marks
+                .push(scope::peek_stack_frame_local_slot_counter());
+        })
+    }
+
+    /// Adds an anonymous scope for the body of a loop.
+    /// Loop scopes must be ended with `end_loop_scope`.
+    pub fn add_loop_scope() {
+        Self::add_anonymous_scope();
+        SYMBOL_TABLE.with(|symbol_table| {
+            symbol_table.borrow_mut().loop_depth += 1;
+        })
+    }
+
+    pub fn end_loop_scope() {
+        Self::end_curr_scope();
+        SYMBOL_TABLE.with(|symbol_table| {
+            symbol_table.borrow_mut().loop_depth -= 1;
+        })
+    }
+
+    /// Returns true if the active scope is
+    /// nested within the body of a loop.
+    pub fn is_in_loop_scope() -> bool {
+        SYMBOL_TABLE.with(|symbol_table| symbol_table.borrow().loop_depth > 0)
+    }
+
+    /// Ends the scope of the function being parsed. The scopes
+    /// of blocks in the function that are still active, which
+    /// happens when the parser recovers from a syntax error in
+    /// the header of a block, are ended along with it.
+    pub fn end_function_scope() {
+        while SYMBOL_TABLE.with(|symbol_table| {
+            symbol_table
+                .borrow()
+                .scope_tree
+                .active_scope()
+                .borrow()
+                .is_anonymous()
+        }) {
+            Self::end_curr_scope();
+        }
+        Self::end_curr_scope();
+
+        SYMBOL_TABLE.with(|symbol_table| {
+            symbol_table.borrow_mut().loop_depth = 0;
         })
     }
 
@@ -84,7 +267,85 @@ impl SymbolTable {
 
     pub fn end_curr_scope() {
         SYMBOL_TABLE.with(|symbol_table| {
-            symbol_table.borrow_mut().scope_tree.end_curr_scope();
+            let mut symbol_table = symbol_table.borrow_mut();
+
+            // The stack slots of the locals of a block are
+            // reused by the blocks that follow it.
+            if symbol_table
+                .scope_tree
+                .active_scope()
+                .borrow()
+                .is_anonymous()
+            {
+                if let Some(mark) = symbol_table.block_slot_marks.pop() {
+                    scope::release_stack_frame_local_slots_from(mark);
+                }
+            }
+
+            symbol_table.scope_tree.end_curr_scope();
+        })
+    }
+
+    /// Reserves `num_slots` consecutive stack slots for a local
+    /// variable of type `num_type`, and returns the index of the
+    /// first one. Every element of a local array takes up a stack
+    /// slot of its own. A slot reused by a sibling block keeps
+    /// the type it was first reserved with, as stack slots in
+    /// Tiny are untyped.
+    pub fn add_local_slots(num_type: NumType, num_slots: usize) -> usize {
+        let index = scope::get_stack_frame_local_slot_counter(num_slots);
+
+        SYMBOL_TABLE.with(|symbol_table| {
+            let local_slots = &mut symbol_table.borrow_mut().local_slots;
+            let num_new_slots = (index - 1 + num_slots).saturating_sub(local_slots.len());
+            local_slots.extend(std::iter::repeat_n(num_type, num_new_slots));
+        });
+
+        index
+    }
+
+    /// Reserves consecutive stack slots for the fields of a
+    /// local RECORD of type `record_type`, one per field, and
+    /// returns the index of the first one.
+    pub fn add_record_local_slots(record_type: &data::RecordType) -> usize {
+        let index = scope::get_stack_frame_local_slot_counter(record_type.size());
+
+        SYMBOL_TABLE.with(|symbol_table| {
+            let local_slots = &mut symbol_table.borrow_mut().local_slots;
+            for (offset, &(_, num_type)) in record_type.fields().iter().enumerate() {
+                if local_slots.len() < index + offset {
+                    local_slots.push(num_type);
+                }
+            }
+        });
+
+        index
+    }
+
+    /// Returns the types of the local stack slots
+    /// taken up so far by the function being parsed.
+    pub fn local_slots() -> Vec<NumType> {
+        SYMBOL_TABLE.with(|symbol_table| symbol_table.borrow().local_slots.clone())
+    }
+
+    /// Updates the locals of the function `symbol` with all of
+    /// the local stack slots taken up by its body, including
+    /// the ones of its nested blocks, and starts afresh for
+    /// the next function.
+    pub fn end_function_locals(symbol: Rc<function::Symbol>) -> Rc<function::Symbol> {
+        SYMBOL_TABLE.with(|symbol_table| {
+            let mut symbol_table = symbol_table.borrow_mut();
+            let local_slots = std::mem::take(&mut symbol_table.local_slots);
+
+            if local_slots.len() == symbol.num_locals() {
+                return symbol;
+            }
+
+            symbol_table
+                .scope_tree
+                .global_scope()
+                .borrow_mut()
+                .replace_function_symbol(symbol.with_locals(local_slots))
         })
     }
 
@@ -117,19 +378,129 @@ impl SymbolTable {
         })
     }
 
-    // TODO [unit tests]: add relevant unit tests
+    /// Adds the symbol of a function definition. If the
+    /// function has been declared by a prototype, the
+    /// definition replaces the prototype and must match
+    /// its signature.
     pub fn add_function_symbol(symbol: function::Symbol) -> Result<(), SymbolError> {
         SYMBOL_TABLE.with(|symbol_table| {
-            let scope_tree = &mut symbol_table.borrow_mut().scope_tree;
+            let mut symbol_table = symbol_table.borrow_mut();
             // Functions can only be declared in global scope
-            scope_tree
-                .global_scope()
+            let global_scope = symbol_table.scope_tree.global_scope();
+
+            if symbol_table
+                .undefined_functions
+                .remove(symbol.name())
+                .is_some()
+            {
+                let prototype = global_scope
+                    .borrow()
+                    .function_symbol_for_name(symbol.name())?;
+
+                if prototype.return_type() != symbol.return_type()
+                    || prototype.params() != symbol.params()
+                {
+                    return Err(SymbolError::MismatchedFunctionPrototype(
+                        MismatchedFunctionPrototypeError::new(symbol.name().to_owned()),
+                    ));
+                }
+
+                global_scope.borrow_mut().replace_function_symbol(symbol);
+                return Ok(());
+            }
+
+            global_scope.borrow_mut().add_function_symbol(symbol)?;
+            Ok(())
+        })
+    }
+
+    /// Adds the symbol of a function prototype, which
+    /// allows calls to the function ahead of its definition.
+    pub fn add_function_prototype(symbol: function::Symbol) -> Result<(), SymbolError> {
+        if Self::function_symbol_for_name(symbol.name()).is_ok() {
+            return Err(SymbolError::DeclareExistingSymbol(
+                DeclareExistingSymbolError::new("GLOBAL".to_owned(), symbol.name().to_owned()),
+            ));
+        }
+
+        let name = symbol.name().to_owned();
+        Self::add_function_symbol(symbol)?;
+        SYMBOL_TABLE.with(|symbol_table| {
+            symbol_table
                 .borrow_mut()
-                .add_function_symbol(symbol)?;
+                .undefined_functions
+                .insert(name, None);
+        });
+        Ok(())
+    }
+
+    /// Declares the RECORD type `record_type`.
+    pub fn add_record_type(record_type: data::RecordType) -> Result<(), SymbolError> {
+        SYMBOL_TABLE.with(|symbol_table| {
+            let record_types = &mut symbol_table.borrow_mut().record_types;
+
+            if record_types.contains_key(record_type.name()) {
+                return Err(SymbolError::DeclareExistingSymbol(
+                    DeclareExistingSymbolError::new(
+                        "GLOBAL".to_owned(),
+                        record_type.name().to_owned(),
+                    ),
+                ));
+            }
+
+            record_types.insert(record_type.name().to_owned(), Rc::new(record_type));
             Ok(())
         })
     }
 
+    pub fn record_type_for_name(name: &str) -> Result<Rc<data::RecordType>, SymbolError> {
+        SYMBOL_TABLE.with(|symbol_table| {
+            symbol_table
+                .borrow()
+                .record_types
+                .get(name)
+                .cloned()
+                .ok_or_else(|| {
+                    SymbolError::UseUndeclaredSymbol(UseUndeclaredSymbolError::new(name.to_owned()))
+                })
+        })
+    }
+
+    /// Records a call to the function `name` at `span`, so that
+    /// calls to functions that are never defined can be reported.
+    pub fn add_function_call(name: &str, span: Span) {
+        SYMBOL_TABLE.with(|symbol_table| {
+            if let Some(call) = symbol_table.borrow_mut().undefined_functions.get_mut(name) {
+                call.get_or_insert(span);
+            }
+        })
+    }
+
+    /// Returns the names of the functions that have not been
+    /// defined (yet), along with where they are first called,
+    /// if they are called.
+    pub fn undefined_functions() -> Vec<(String, Option<Span>)> {
+        SYMBOL_TABLE.with(|symbol_table| {
+            symbol_table
+                .borrow()
+                .undefined_functions
+                .iter()
+                .map(|(name, call)| (name.clone(), *call))
+                .collect()
+        })
+    }
+
+    pub fn add_warning(warning: Diagnostic) {
+        SYMBOL_TABLE.with(|symbol_table| {
+            symbol_table.borrow_mut().warnings.push(warning);
+        })
+    }
+
+    /// Returns the warnings reported while parsing the program.
+    pub fn warnings() -> Vec<Diagnostic> {
+        SYMBOL_TABLE.with(|symbol_table| symbol_table.borrow().warnings.clone())
+    }
+
     // TODO [unit tests]: add relevant unit tests
     pub fn data_symbol_for_name(symbol_name: &str) -> Result<data::Symbol, SymbolError> {
         SYMBOL_TABLE.with(|symbol_table| {
@@ -237,10 +608,7 @@ mod test {
         SYMBOL_TABLE.with(|symbol_table| {
             let mut symbol_table = symbol_table.borrow_mut();
 
-            *symbol_table = SymbolTable {
-                scope_tree: ScopeTree::new(),
-                symbol_errors: vec![],
-            };
+            *symbol_table = SymbolTable::new();
 
             ANONYMOUS_SCOPE_COUNTER.store(1, Ordering::SeqCst);
         })
@@ -343,4 +711,18 @@ mod test {
             .err()
             .is_some());
     }
+
+    #[test]
+    #[serial]
+    fn identical_string_literals_are_interned_once() {
+        setup();
+
+        let hello = SymbolTable::intern_string_literal("\"hello\"");
+        let world = SymbolTable::intern_string_literal("\"world\"");
+        let hello_again = SymbolTable::intern_string_literal("\"hello\"");
+
+        assert_eq!(hello, hello_again);
+        assert_ne!(hello, world);
+        assert_eq!(2, SymbolTable::string_literals().len());
+    }
 }
@@ -20,16 +20,31 @@ pub fn reset_stack_frame_local_slot_counter() {
     STACK_FRAME_LOCAL_SLOT_COUNTER.store(1, Ordering::SeqCst);
 }
 
-pub fn get_stack_frame_local_slot_counter() -> usize {
-    STACK_FRAME_LOCAL_SLOT_COUNTER.fetch_add(1, Ordering::SeqCst)
+/// Reserves `num_slots` consecutive stack slots for a local
+/// variable and returns the index of the first one.
+pub fn get_stack_frame_local_slot_counter(num_slots: usize) -> usize {
+    STACK_FRAME_LOCAL_SLOT_COUNTER.fetch_add(num_slots, Ordering::SeqCst)
+}
+
+/// Returns the index of the next free local stack slot.
+pub fn peek_stack_frame_local_slot_counter() -> usize {
+    STACK_FRAME_LOCAL_SLOT_COUNTER.load(Ordering::SeqCst)
+}
+
+/// Frees all local stack slots starting at `slot`,
+/// so that they can be reused by other locals.
+pub fn release_stack_frame_local_slots_from(slot: usize) {
+    STACK_FRAME_LOCAL_SLOT_COUNTER.store(slot, Ordering::SeqCst);
 }
 
 pub fn reset_stack_frame_param_slot_counter() {
     STACK_FRAME_PARAM_SLOT_COUNTER.store(1, Ordering::SeqCst);
 }
 
-pub fn get_stack_frame_param_slot_counter() -> usize {
-    STACK_FRAME_PARAM_SLOT_COUNTER.fetch_add(1, Ordering::SeqCst)
+/// Reserves `num_slots` consecutive stack slots for a
+/// parameter and returns the index of the first one.
+pub fn get_stack_frame_param_slot_counter(num_slots: usize) -> usize {
+    STACK_FRAME_PARAM_SLOT_COUNTER.fetch_add(num_slots, Ordering::SeqCst)
 }
 
 #[derive(Debug, Eq, PartialEq)]
@@ -42,11 +57,17 @@ pub(crate) enum Scope {
         name: String,
         parent: Rc<RefCell<Scope>>,
         data_symbols: LinkedHashSet<Rc<data::NonFunctionScopedSymbol>>,
+        /// Locals of a block nested in a function,
+        /// which live in the function's stack frame.
+        func_scoped_symbols: LinkedHashMap<String, Rc<data::FunctionScopedSymbol>>,
     },
     Function {
         name: String,
         parent: Rc<RefCell<Scope>>,
         data_symbols: LinkedHashMap<String, Rc<data::FunctionScopedSymbol>>,
+        /// CONST symbols declared in the function,
+        /// which take up no stack slot.
+        constants: LinkedHashSet<Rc<data::NonFunctionScopedSymbol>>,
     },
 }
 
@@ -60,17 +81,29 @@ impl Display for Scope {
                     .try_for_each(|symbol| writeln!(f, "{}", symbol))?;
             }
             Scope::Anonymous {
-                name, data_symbols, ..
+                name,
+                data_symbols,
+                func_scoped_symbols,
+                ..
             } => {
                 writeln!(f, "Symbol table {}", self.name())?;
                 data_symbols
                     .iter()
                     .try_for_each(|symbol| writeln!(f, "{}", symbol))?;
+                func_scoped_symbols
+                    .iter()
+                    .try_for_each(|(name, symbol)| writeln!(f, "{} : {}", name, symbol))?;
             }
             Scope::Function {
-                name, data_symbols, ..
+                name,
+                data_symbols,
+                constants,
+                ..
             } => {
                 writeln!(f, "Symbol table {}", self.name())?;
+                constants
+                    .iter()
+                    .try_for_each(|symbol| writeln!(f, "{}", symbol))?;
                 data_symbols
                     .iter()
                     .try_for_each(|(name, symbol)| writeln!(f, "{} : {}", name, symbol))?;
@@ -93,6 +126,7 @@ impl Scope {
             name: name.to_string(),
             parent,
             data_symbols: LinkedHashSet::new(),
+            func_scoped_symbols: LinkedHashMap::new(),
         }
     }
 
@@ -101,6 +135,7 @@ impl Scope {
             name: name.to_string(),
             parent,
             data_symbols: LinkedHashMap::new(),
+            constants: LinkedHashSet::new(),
         }
     }
 
@@ -111,6 +146,20 @@ impl Scope {
         }
     }
 
+    pub(crate) fn is_anonymous(&self) -> bool {
+        matches!(self, Scope::Anonymous { .. })
+    }
+
+    /// Returns true if the scope is a function
+    /// scope, or is nested within one.
+    pub(crate) fn is_in_function(&self) -> bool {
+        match self {
+            Scope::Global { .. } => false,
+            Scope::Anonymous { parent, .. } => parent.borrow().is_in_function(),
+            Scope::Function { .. } => true,
+        }
+    }
+
     pub(crate) fn global_symbols(&self) -> Vec<Rc<data::NonFunctionScopedSymbol>> {
         // TODO [rust type system]: Can I statically assert that
         //  self is the `Global` variant of `Scope`?
@@ -120,6 +169,29 @@ impl Scope {
         }
     }
 
+    pub(crate) fn has_array_symbols(&self) -> bool {
+        match self {
+            Scope::Global { data_symbols, .. } => data_symbols
+                .iter()
+                .any(|symbol| symbol.array_size().is_some()),
+            Scope::Anonymous {
+                data_symbols,
+                func_scoped_symbols,
+                ..
+            } => {
+                data_symbols
+                    .iter()
+                    .any(|symbol| symbol.array_size().is_some())
+                    || func_scoped_symbols
+                        .values()
+                        .any(|symbol| symbol.array_size().is_some())
+            }
+            Scope::Function { data_symbols, .. } => data_symbols
+                .values()
+                .any(|symbol| symbol.array_size().is_some()),
+        }
+    }
+
     pub(crate) fn add_function_symbol(
         &mut self,
         symbol: function::Symbol,
@@ -144,6 +216,37 @@ impl Scope {
         Ok(())
     }
 
+    /// Replaces the function symbol with the same
+    /// name as `symbol`, keeping its position.
+    pub(crate) fn replace_function_symbol(
+        &mut self,
+        symbol: function::Symbol,
+    ) -> Rc<function::Symbol> {
+        let symbol = Rc::new(symbol);
+
+        match self {
+            Scope::Global {
+                function_symbols, ..
+            } => {
+                *function_symbols = function_symbols
+                    .iter()
+                    .map(|existing| {
+                        if existing.name() == symbol.name() {
+                            Rc::clone(&symbol)
+                        } else {
+                            Rc::clone(existing)
+                        }
+                    })
+                    .collect();
+            }
+            Scope::Anonymous { .. } | Scope::Function { .. } => {
+                unreachable!("A function symbol should never be added to a non-GLOBAL scope!")
+            }
+        };
+
+        symbol
+    }
+
     pub(crate) fn add_non_func_scoped_symbol(
         &mut self,
         symbol: data::NonFunctionScopedSymbol,
@@ -155,6 +258,11 @@ impl Scope {
         }
 
         match self {
+            Scope::Function { constants, .. }
+                if matches!(symbol, data::NonFunctionScopedSymbol::Const { .. }) =>
+            {
+                constants.insert(Rc::new(symbol))
+            }
             Scope::Function { .. } => {
                 return Err(SymbolError::DeclareInInvalidSymbolScope(
                     DeclareInInvalidScopeError::new(
@@ -183,7 +291,12 @@ impl Scope {
             ));
         }
 
+        let is_in_function = self.is_in_function();
         match self {
+            Scope::Anonymous {
+                func_scoped_symbols,
+                ..
+            } if is_in_function => func_scoped_symbols.insert(name, Rc::new(symbol)),
             Scope::Global { .. } | Scope::Anonymous { .. } => {
                 let scope_type = if let Scope::Anonymous { .. } = self {
                     ScopeType::Anonymous
@@ -220,26 +333,35 @@ impl Scope {
                 }),
             Scope::Anonymous {
                 data_symbols,
+                func_scoped_symbols,
                 parent,
                 ..
             } => data_symbols
                 .iter()
                 .find(|&symbol| symbol.name() == symbol_name)
-                .map_or_else(
-                    || parent.borrow().data_symbol_for_name(symbol_name),
-                    |symbol| Ok(data::Symbol::NonFunctionScopedSymbol(symbol.clone())),
-                ),
+                .map(|symbol| data::Symbol::NonFunctionScopedSymbol(symbol.clone()))
+                .or_else(|| {
+                    func_scoped_symbols
+                        .get(symbol_name)
+                        .map(|symbol| data::Symbol::FunctionScopedSymbol(symbol.clone()))
+                })
+                .map_or_else(|| parent.borrow().data_symbol_for_name(symbol_name), Ok),
             Scope::Function {
                 data_symbols,
+                constants,
                 parent,
                 ..
             } => data_symbols
                 .iter()
                 .find(|&(name, symbol)| name == symbol_name)
-                .map_or_else(
-                    || parent.borrow().data_symbol_for_name(symbol_name),
-                    |(name, symbol)| Ok(data::Symbol::FunctionScopedSymbol(symbol.clone())),
-                ),
+                .map(|(name, symbol)| data::Symbol::FunctionScopedSymbol(symbol.clone()))
+                .or_else(|| {
+                    constants
+                        .iter()
+                        .find(|&symbol| symbol.name() == symbol_name)
+                        .map(|symbol| data::Symbol::NonFunctionScopedSymbol(symbol.clone()))
+                })
+                .map_or_else(|| parent.borrow().data_symbol_for_name(symbol_name), Ok),
         }
     }
 
@@ -270,13 +392,34 @@ impl Scope {
             Scope::Global { data_symbols, .. } | Scope::Anonymous { data_symbols, .. } => {
                 data_symbols.contains(symbol)
             }
-            _ => false,
+            // Constants share their namespace with the locals of the function
+            Scope::Function {
+                data_symbols,
+                constants,
+                ..
+            } => {
+                data_symbols.contains_key(symbol.name())
+                    || constants
+                        .iter()
+                        .any(|constant| constant.name() == symbol.name())
+            }
         }
     }
 
     fn contains_func_scoped_symbol(&self, name: &str) -> bool {
         match self {
-            Scope::Function { data_symbols, .. } => data_symbols.contains_key(name),
+            Scope::Function {
+                data_symbols,
+                constants,
+                ..
+            } => {
+                data_symbols.contains_key(name)
+                    || constants.iter().any(|symbol| symbol.name() == name)
+            }
+            Scope::Anonymous {
+                func_scoped_symbols,
+                ..
+            } => func_scoped_symbols.contains_key(name),
             _ => false,
         }
     }
@@ -302,6 +445,7 @@ impl Scope {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::span::Span;
     use crate::symbol_table::error::ScopeType::Anonymous;
     use crate::symbol_table::symbol::data::FunctionScopedSymbolType;
     use crate::symbol_table::symbol::function::ReturnType;
@@ -318,8 +462,13 @@ mod test {
 
     #[test]
     fn add_func_symbol_under_invalid_scope_returns_invalid_scope_declaration_error() {
-        let symbol =
-            function::Symbol::new("some_func".to_owned(), ReturnType::Void, vec![], vec![]);
+        let symbol = function::Symbol::new(
+            "some_func".to_owned(),
+            ReturnType::Void,
+            vec![],
+            vec![],
+            Span::default(),
+        );
 
         let global = Rc::new(RefCell::new(Scope::new_global()));
         let mut anonymous = Scope::new_anonymous("Anonymous", global);
@@ -335,8 +484,13 @@ mod test {
 
     #[test]
     fn add_existing_func_symbol_under_valid_scope_returns_symbol_redeclaration_error() {
-        let symbol =
-            function::Symbol::new("some_func".to_owned(), ReturnType::Void, vec![], vec![]);
+        let symbol = function::Symbol::new(
+            "some_func".to_owned(),
+            ReturnType::Void,
+            vec![],
+            vec![],
+            Span::default(),
+        );
 
         let mut global = Scope::new_global();
         global.add_function_symbol(symbol.clone());
@@ -366,6 +520,22 @@ mod test {
         );
     }
 
+    #[test]
+    fn const_symbol_under_function_scope_is_found_by_name() {
+        let symbol = data::NonFunctionScopedSymbol::Const {
+            name: "symbol".to_owned(),
+            value: data::NumValue::Int(42),
+        };
+        let global = Rc::new(RefCell::new(Scope::new_global()));
+        let mut function = Scope::new_function("Function", global);
+
+        function.add_non_func_scoped_symbol(symbol.clone()).unwrap();
+        assert_eq!(
+            data::Symbol::NonFunctionScopedSymbol(Rc::new(symbol)),
+            function.data_symbol_for_name("symbol").unwrap()
+        );
+    }
+
     #[test]
     fn add_existing_non_func_scoped_symbol_under_valid_scope_returns_symbol_redeclaration_error() {
         let symbol = data::NonFunctionScopedSymbol::Int {
@@ -403,6 +573,25 @@ mod test {
         );
     }
 
+    #[test]
+    fn func_scoped_symbol_under_anonymous_scope_in_function_is_found_by_name() {
+        let symbol = data::FunctionScopedSymbol::Int {
+            symbol_type: FunctionScopedSymbolType::Local,
+            index: 42,
+        };
+        let global = Rc::new(RefCell::new(Scope::new_global()));
+        let function = Rc::new(RefCell::new(Scope::new_function("Function", global)));
+        let mut anonymous = Scope::new_anonymous("Anonymous", function);
+
+        anonymous
+            .add_func_scoped_symbol("symbol".to_owned(), symbol.clone())
+            .unwrap();
+        assert_eq!(
+            data::Symbol::FunctionScopedSymbol(Rc::new(symbol)),
+            anonymous.data_symbol_for_name("symbol").unwrap()
+        );
+    }
+
     #[test]
     fn add_existing_func_scoped_symbol_under_valid_scope_returns_symbol_redeclaration_error() {
         let symbol = data::FunctionScopedSymbol::Int {
@@ -504,8 +693,13 @@ mod test {
 
     #[test]
     fn func_symbol_for_name_returns_symbol_when_symbol_and_scope_valid() {
-        let symbol =
-            function::Symbol::new("some_func".to_owned(), ReturnType::Void, vec![], vec![]);
+        let symbol = function::Symbol::new(
+            "some_func".to_owned(),
+            ReturnType::Void,
+            vec![],
+            vec![],
+            Span::default(),
+        );
         let mut global = Scope::new_global();
         global.add_function_symbol(symbol.clone());
         assert_eq!(
@@ -516,8 +710,13 @@ mod test {
 
     #[test]
     fn func_symbol_for_name_returns_undeclared_symbol_error_when_symbol_undeclared() {
-        let symbol =
-            function::Symbol::new("some_func".to_owned(), ReturnType::Void, vec![], vec![]);
+        let symbol = function::Symbol::new(
+            "some_func".to_owned(),
+            ReturnType::Void,
+            vec![],
+            vec![],
+            Span::default(),
+        );
         let mut global = Scope::new_global();
         assert_eq!(
             SymbolError::UseUndeclaredSymbol(UseUndeclaredSymbolError::new("some_func".to_owned())),
@@ -52,6 +52,10 @@ impl ScopeTree {
         self.active_scope_stack.pop();
     }
 
+    pub(crate) fn scopes(&self) -> impl Iterator<Item = &Rc<RefCell<Scope>>> {
+        self.scopes.iter()
+    }
+
     #[cfg(test)]
     pub(crate) fn len(&self) -> usize {
         self.scopes.len()
@@ -1,17 +1,124 @@
-#[derive(Debug, Eq, PartialEq, Copy, Clone, Hash)]
+/// Type of a scalar value. BOOL values are
+/// stored as INTs that are either 0 or 1.
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Hash, derive_more::Display)]
 pub enum NumType {
+    #[display(fmt = "INT")]
     Int,
+    #[display(fmt = "FLOAT")]
     Float,
+    #[display(fmt = "BOOL")]
+    Bool,
 }
 
 pub mod data {
     use crate::symbol_table::symbol::NumType;
+    use std::hash::{Hash, Hasher};
     use std::rc::Rc;
 
     #[derive(Debug, Eq, PartialEq, Copy, Clone)]
     pub enum DataType {
         String,
         Num(NumType),
+        /// Fixed-size array of numbers.
+        Array(NumType),
+        /// Value of a user-defined RECORD type,
+        /// see `Symbol::record_type`.
+        Record,
+    }
+
+    /// A user-defined RECORD type. A RECORD variable is laid out
+    /// as one variable per field - a global named `<variable>.<field>`,
+    /// or a stack slot at the offset of the field from the slot of
+    /// the variable if it is a local or a parameter.
+    #[derive(Debug, PartialEq, Eq, Hash)]
+    pub struct RecordType {
+        name: String,
+        fields: Vec<(String, NumType)>,
+    }
+
+    impl RecordType {
+        pub fn new(name: String, fields: Vec<(String, NumType)>) -> Self {
+            Self { name, fields }
+        }
+
+        pub fn name(&self) -> &str {
+            &self.name
+        }
+
+        /// Returns the fields of the record, in the
+        /// order they are laid out in memory.
+        pub fn fields(&self) -> &[(String, NumType)] {
+            &self.fields
+        }
+
+        /// Returns the number of stack slots a value
+        /// of the record takes up, one per field.
+        pub fn size(&self) -> usize {
+            self.fields.len()
+        }
+    }
+
+    /// A numeric value known at compile time.
+    #[derive(Debug, Copy, Clone, derive_more::Display)]
+    pub enum NumValue {
+        Int(i32),
+        Float(f64),
+        #[display(fmt = "{}", "*_0 as i32")]
+        Bool(bool),
+    }
+
+    // FLOAT values are compared bit for bit, so that
+    // symbols holding a value can be hashed.
+    impl PartialEq for NumValue {
+        fn eq(&self, other: &Self) -> bool {
+            match (self, other) {
+                (NumValue::Int(lhs), NumValue::Int(rhs)) => lhs == rhs,
+                (NumValue::Float(lhs), NumValue::Float(rhs)) => lhs.to_bits() == rhs.to_bits(),
+                (NumValue::Bool(lhs), NumValue::Bool(rhs)) => lhs == rhs,
+                _ => false,
+            }
+        }
+    }
+
+    impl Eq for NumValue {}
+
+    impl Hash for NumValue {
+        fn hash<H: Hasher>(&self, state: &mut H) {
+            match self {
+                NumValue::Int(n) => n.hash(state),
+                NumValue::Float(n) => n.to_bits().hash(state),
+                NumValue::Bool(b) => b.hash(state),
+            }
+        }
+    }
+
+    impl NumValue {
+        pub fn num_type(&self) -> NumType {
+            match self {
+                NumValue::Int(_) => NumType::Int,
+                NumValue::Float(_) => NumType::Float,
+                NumValue::Bool(_) => NumType::Bool,
+            }
+        }
+
+        pub fn as_float(self) -> f64 {
+            match self {
+                NumValue::Int(n) => n as f64,
+                NumValue::Float(n) => n,
+                NumValue::Bool(b) => b as i32 as f64,
+            }
+        }
+
+        /// Converts the value to `num_type`. FLOAT
+        /// values are truncated towards zero when
+        /// converted to INT.
+        pub fn convert_to(self, num_type: NumType) -> NumValue {
+            match (self, num_type) {
+                (NumValue::Int(n), NumType::Float) => NumValue::Float(n as f64),
+                (NumValue::Float(n), NumType::Int) => NumValue::Int(n as i32),
+                (value, _) => value,
+            }
+        }
     }
 
     /// Represents a symbol declared in the program.
@@ -24,6 +131,75 @@ pub mod data {
         FunctionScopedSymbol(Rc<FunctionScopedSymbol>),
     }
 
+    impl Symbol {
+        /// Returns the number of elements
+        /// if the symbol is an array.
+        pub fn array_size(&self) -> Option<usize> {
+            match self {
+                Symbol::NonFunctionScopedSymbol(symbol) => symbol.array_size(),
+                Symbol::FunctionScopedSymbol(symbol) => symbol.array_size(),
+            }
+        }
+
+        /// Returns the value of the symbol
+        /// if the symbol is a constant.
+        pub fn const_value(&self) -> Option<NumValue> {
+            match self {
+                Symbol::NonFunctionScopedSymbol(symbol) => symbol.const_value(),
+                Symbol::FunctionScopedSymbol(_) => None,
+            }
+        }
+
+        /// Returns the symbol of the variable holding `field`,
+        /// if the symbol is a RECORD with such a field.
+        pub fn field_symbol(&self, field: &str) -> Option<Symbol> {
+            let offset = self
+                .record_type()?
+                .fields()
+                .iter()
+                .position(|(name, _)| name == field)?;
+
+            Some(self.field_symbols().swap_remove(offset))
+        }
+
+        /// Returns the symbols of the variables holding the
+        /// fields of the symbol, in the order they are laid
+        /// out in memory, if the symbol is a RECORD.
+        pub fn field_symbols(&self) -> Vec<Symbol> {
+            match self {
+                Symbol::NonFunctionScopedSymbol(symbol) => symbol
+                    .field_symbols()
+                    .into_iter()
+                    .map(|symbol| Rc::new(symbol).into())
+                    .collect(),
+                Symbol::FunctionScopedSymbol(symbol) => symbol
+                    .field_symbols()
+                    .into_iter()
+                    .map(|symbol| Rc::new(symbol).into())
+                    .collect(),
+            }
+        }
+
+        /// Returns the type of the symbol
+        /// if the symbol is a RECORD.
+        pub fn record_type(&self) -> Option<Rc<RecordType>> {
+            match self {
+                Symbol::NonFunctionScopedSymbol(symbol) => match &**symbol {
+                    NonFunctionScopedSymbol::Record { record_type, .. } => {
+                        Some(Rc::clone(record_type))
+                    }
+                    _ => None,
+                },
+                Symbol::FunctionScopedSymbol(symbol) => match &**symbol {
+                    FunctionScopedSymbol::Record { record_type, .. } => {
+                        Some(Rc::clone(record_type))
+                    }
+                    _ => None,
+                },
+            }
+        }
+    }
+
     impl From<Rc<NonFunctionScopedSymbol>> for Symbol {
         fn from(symbol: Rc<NonFunctionScopedSymbol>) -> Self {
             Symbol::NonFunctionScopedSymbol(symbol)
@@ -47,14 +223,46 @@ pub mod data {
         Int { name: String },
         #[display(fmt = "{}", name)]
         Float { name: String },
+        #[display(fmt = "{}", name)]
+        Bool { name: String },
+        #[display(fmt = "{}", name)]
+        IntArray { name: String, size: usize },
+        #[display(fmt = "{}", name)]
+        FloatArray { name: String, size: usize },
+        /// Named constant, whose value is
+        /// substituted for it at compile time.
+        #[display(fmt = "{}", name)]
+        Const { name: String, value: NumValue },
+        /// RECORD variable, whose fields are
+        /// global variables of their own.
+        #[display(fmt = "{}", name)]
+        Record {
+            name: String,
+            record_type: Rc<RecordType>,
+        },
     }
 
     impl NonFunctionScopedSymbol {
+        /// Returns the symbol of a global variable
+        /// named `name` holding a `num_type` value.
+        pub fn new_num(name: String, num_type: NumType) -> Self {
+            match num_type {
+                NumType::Int => NonFunctionScopedSymbol::Int { name },
+                NumType::Float => NonFunctionScopedSymbol::Float { name },
+                NumType::Bool => NonFunctionScopedSymbol::Bool { name },
+            }
+        }
+
         pub fn name(&self) -> &str {
             match self {
                 NonFunctionScopedSymbol::String { name, value } => name,
                 NonFunctionScopedSymbol::Int { name } => name,
                 NonFunctionScopedSymbol::Float { name } => name,
+                NonFunctionScopedSymbol::Bool { name } => name,
+                NonFunctionScopedSymbol::IntArray { name, .. } => name,
+                NonFunctionScopedSymbol::FloatArray { name, .. } => name,
+                NonFunctionScopedSymbol::Const { name, .. } => name,
+                NonFunctionScopedSymbol::Record { name, .. } => name,
             }
         }
 
@@ -63,6 +271,45 @@ pub mod data {
                 NonFunctionScopedSymbol::String { .. } => DataType::String,
                 NonFunctionScopedSymbol::Int { .. } => DataType::Num(NumType::Int),
                 NonFunctionScopedSymbol::Float { .. } => DataType::Num(NumType::Float),
+                NonFunctionScopedSymbol::Bool { .. } => DataType::Num(NumType::Bool),
+                NonFunctionScopedSymbol::IntArray { .. } => DataType::Array(NumType::Int),
+                NonFunctionScopedSymbol::FloatArray { .. } => DataType::Array(NumType::Float),
+                NonFunctionScopedSymbol::Const { value, .. } => DataType::Num(value.num_type()),
+                NonFunctionScopedSymbol::Record { .. } => DataType::Record,
+            }
+        }
+
+        /// Returns the value of the symbol
+        /// if the symbol is a constant.
+        pub fn const_value(&self) -> Option<NumValue> {
+            match self {
+                NonFunctionScopedSymbol::Const { value, .. } => Some(*value),
+                _ => None,
+            }
+        }
+
+        /// Returns the number of elements
+        /// if the symbol is an array.
+        pub fn array_size(&self) -> Option<usize> {
+            match self {
+                NonFunctionScopedSymbol::IntArray { size, .. }
+                | NonFunctionScopedSymbol::FloatArray { size, .. } => Some(*size),
+                _ => None,
+            }
+        }
+
+        /// Returns the globals holding the fields
+        /// of the symbol if the symbol is a RECORD.
+        pub fn field_symbols(&self) -> Vec<NonFunctionScopedSymbol> {
+            match self {
+                NonFunctionScopedSymbol::Record { name, record_type } => record_type
+                    .fields()
+                    .iter()
+                    .map(|(field, num_type)| {
+                        NonFunctionScopedSymbol::new_num(format!("{}.{}", name, field), *num_type)
+                    })
+                    .collect(),
+                _ => vec![],
             }
         }
     }
@@ -84,7 +331,10 @@ pub mod data {
     /// Represents a symbol in the scope of a
     /// function. The symbol is either a function
     /// parameter or a local variable and can be
-    /// an int or a float.
+    /// an int, a float or a bool. Local variables can also
+    /// be arrays, which take up `size` consecutive
+    /// stack slots starting at `index`. A RECORD is
+    /// laid out the same way, one stack slot per field.
     #[derive(Debug, PartialEq, Clone, Hash, Eq, derive_more::Display)]
     pub enum FunctionScopedSymbol {
         #[display(fmt = "${}{}", symbol_type, index)]
@@ -97,11 +347,94 @@ pub mod data {
             symbol_type: FunctionScopedSymbolType,
             index: usize,
         },
+        #[display(fmt = "${}{}", symbol_type, index)]
+        Bool {
+            symbol_type: FunctionScopedSymbolType,
+            index: usize,
+        },
+        #[display(fmt = "${}{}", symbol_type, index)]
+        IntArray {
+            symbol_type: FunctionScopedSymbolType,
+            index: usize,
+            size: usize,
+        },
+        #[display(fmt = "${}{}", symbol_type, index)]
+        FloatArray {
+            symbol_type: FunctionScopedSymbolType,
+            index: usize,
+            size: usize,
+        },
+        #[display(fmt = "${}{}", symbol_type, index)]
+        Record {
+            symbol_type: FunctionScopedSymbolType,
+            index: usize,
+            record_type: Rc<RecordType>,
+        },
+    }
+
+    impl FunctionScopedSymbol {
+        /// Returns the symbol of a parameter or a local
+        /// variable holding a `num_type` value.
+        pub fn new_num(
+            symbol_type: FunctionScopedSymbolType,
+            index: usize,
+            num_type: NumType,
+        ) -> Self {
+            match num_type {
+                NumType::Int => FunctionScopedSymbol::Int { symbol_type, index },
+                NumType::Float => FunctionScopedSymbol::Float { symbol_type, index },
+                NumType::Bool => FunctionScopedSymbol::Bool { symbol_type, index },
+            }
+        }
+
+        pub fn data_type(&self) -> DataType {
+            match self {
+                FunctionScopedSymbol::Int { .. } => DataType::Num(NumType::Int),
+                FunctionScopedSymbol::Float { .. } => DataType::Num(NumType::Float),
+                FunctionScopedSymbol::Bool { .. } => DataType::Num(NumType::Bool),
+                FunctionScopedSymbol::IntArray { .. } => DataType::Array(NumType::Int),
+                FunctionScopedSymbol::FloatArray { .. } => DataType::Array(NumType::Float),
+                FunctionScopedSymbol::Record { .. } => DataType::Record,
+            }
+        }
+
+        /// Returns the number of elements
+        /// if the symbol is an array.
+        pub fn array_size(&self) -> Option<usize> {
+            match self {
+                FunctionScopedSymbol::IntArray { size, .. }
+                | FunctionScopedSymbol::FloatArray { size, .. } => Some(*size),
+                _ => None,
+            }
+        }
+
+        /// Returns the stack slots holding the fields
+        /// of the symbol if the symbol is a RECORD.
+        pub fn field_symbols(&self) -> Vec<FunctionScopedSymbol> {
+            match self {
+                FunctionScopedSymbol::Record {
+                    symbol_type,
+                    index,
+                    record_type,
+                } => record_type
+                    .fields()
+                    .iter()
+                    .enumerate()
+                    .map(|(offset, &(_, num_type))| {
+                        FunctionScopedSymbol::new_num(symbol_type.clone(), index + offset, num_type)
+                    })
+                    .collect(),
+                _ => vec![],
+            }
+        }
     }
 }
 
 pub mod function {
-    use crate::symbol_table::symbol::NumType;
+    use crate::span::Span;
+    use crate::symbol_table::symbol::{data, NumType};
+    use std::hash::{Hash, Hasher};
+    use std::rc::Rc;
 
     /// Represents possible return types
     /// in a function.
@@ -111,29 +444,93 @@ pub mod function {
         Void,
     }
 
+    /// Type of a function parameter. A RECORD is passed
+    /// by value, taking up one stack slot per field.
+    #[derive(Debug, Eq, PartialEq, Clone, Hash)]
+    pub enum ParamType {
+        Num(NumType),
+        /// VAR parameter, passed by copy-in/copy-out rather than
+        /// by reference: Tiny has no indirect addressing, so the
+        /// caller pushes the value of the variable passed for it,
+        /// and copies the value of the parameter back to the
+        /// variable once the function returns.
+        ///
+        /// This only differs from passing by reference when the
+        /// variable is also reachable from the function some other
+        /// way. A global passed as a VAR argument is only updated
+        /// once the function returns, overwriting any update the
+        /// function made to the global directly. A variable passed
+        /// for several VAR parameters ends up with the value of the
+        /// first of them.
+        Var(NumType),
+        Record(Rc<data::RecordType>),
+    }
+
+    impl ParamType {
+        /// Returns the types of the stack
+        /// slots the parameter takes up.
+        pub fn slots(&self) -> Vec<NumType> {
+            match self {
+                ParamType::Num(num_type) | ParamType::Var(num_type) => vec![*num_type],
+                ParamType::Record(record_type) => record_type
+                    .fields()
+                    .iter()
+                    .map(|&(_, num_type)| num_type)
+                    .collect(),
+            }
+        }
+    }
+
     /// Represents function or non-data
-    /// symbols in the program.
-    #[derive(Debug, PartialEq, Clone, Hash, Eq, derive_more::Display)]
+    /// symbols in the program. The span only
+    /// records where the function is declared,
+    /// so it is left out of comparisons.
+    #[derive(Debug, Clone, derive_more::Display)]
     #[display(fmt = "{}", name)]
     pub struct Symbol {
         name: String,
         return_type: ReturnType,
-        params: Vec<NumType>,
+        params: Vec<ParamType>,
         locals: Vec<NumType>,
+        // Where the function is declared in the source,
+        // from `FUNCTION` to the end of its parameters.
+        span: Span,
+    }
+
+    impl PartialEq for Symbol {
+        fn eq(&self, other: &Self) -> bool {
+            self.name == other.name
+                && self.return_type == other.return_type
+                && self.params == other.params
+                && self.locals == other.locals
+        }
+    }
+
+    impl Eq for Symbol {}
+
+    impl Hash for Symbol {
+        fn hash<H: Hasher>(&self, state: &mut H) {
+            self.name.hash(state);
+            self.return_type.hash(state);
+            self.params.hash(state);
+            self.locals.hash(state);
+        }
     }
 
     impl Symbol {
         pub fn new(
             name: String,
             return_type: ReturnType,
-            params: Vec<NumType>,
+            params: Vec<ParamType>,
             locals: Vec<NumType>,
+            span: Span,
         ) -> Self {
             Self {
                 name,
                 return_type,
                 params,
                 locals,
+                span,
             }
         }
 
@@ -141,12 +538,98 @@ pub mod function {
             &self.name
         }
 
+        pub fn span(&self) -> Span {
+            self.span
+        }
+
         pub fn return_type(&self) -> ReturnType {
             self.return_type
         }
 
+        pub fn params(&self) -> &[ParamType] {
+            &self.params
+        }
+
+        /// Returns the types of the stack slots taken up by
+        /// the parameters, which is what the caller pushes.
+        pub fn param_slots(&self) -> Vec<NumType> {
+            self.params.iter().flat_map(ParamType::slots).collect()
+        }
+
+        /// Returns whether each of the stack slots taken
+        /// up by the parameters is a VAR parameter.
+        pub fn var_param_slots(&self) -> Vec<bool> {
+            self.params
+                .iter()
+                .flat_map(|param| {
+                    let is_var = matches!(param, ParamType::Var(_));
+                    param.slots().into_iter().map(move |_| is_var)
+                })
+                .collect()
+        }
+
         pub fn num_locals(&self) -> usize {
             self.locals.len()
         }
+
+        /// Returns a copy of the symbol with the
+        /// stack slots of its locals set to `locals`.
+        pub fn with_locals(&self, locals: Vec<NumType>) -> Self {
+            Self {
+                locals,
+                ..self.clone()
+            }
+        }
+
+        /// Returns the symbol for the stack slot the function
+        /// stores its return value in, if it returns a value.
+        /// The return value is laid out as the first parameter
+        /// of the function - see `func_prologue` in the grammar.
+        pub fn return_value_symbol(&self) -> Option<data::FunctionScopedSymbol> {
+            let symbol_type =
+                data::FunctionScopedSymbolType::Parameter(self.param_slots().len() + 1);
+
+            match self.return_type {
+                ReturnType::Num(NumType::Int) => Some(data::FunctionScopedSymbol::Int {
+                    symbol_type,
+                    index: 1,
+                }),
+                ReturnType::Num(NumType::Float) => Some(data::FunctionScopedSymbol::Float {
+                    symbol_type,
+                    index: 1,
+                }),
+                ReturnType::Num(NumType::Bool) => Some(data::FunctionScopedSymbol::Bool {
+                    symbol_type,
+                    index: 1,
+                }),
+                ReturnType::Void => None,
+            }
+        }
+
+        /// Returns the symbols for the stack slots of the VAR
+        /// parameters of the function, whose values the caller
+        /// copies back to its variables after the function returns.
+        pub fn var_param_symbols(&self) -> Vec<data::FunctionScopedSymbol> {
+            // The parameters are laid out after the return value, if any
+            let first_index = match self.return_type {
+                ReturnType::Num(_) => 2,
+                ReturnType::Void => 1,
+            };
+            let num_params = self.param_slots().len() + first_index - 1;
+
+            self.param_slots()
+                .into_iter()
+                .zip(self.var_param_slots())
+                .enumerate()
+                .filter(|&(_, (_, is_var))| is_var)
+                .map(|(offset, (num_type, _))| {
+                    data::FunctionScopedSymbol::new_num(
+                        data::FunctionScopedSymbolType::Parameter(num_params),
+                        first_index + offset,
+                        num_type,
+                    )
+                })
+                .collect()
+        }
     }
 }
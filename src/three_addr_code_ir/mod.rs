@@ -1,5 +1,5 @@
 //! Three Address Code Intermediate representation.
-//! Type checking should happen at this stage.
+//! The AST it is generated from has been type checked.
 use std::sync::atomic::{AtomicUsize, Ordering};
 
 use crate::ast::ast_node::Identifier;
@@ -152,6 +152,25 @@ impl From<Identifier> for IdentS {
     }
 }
 
+/// Array identifier
+#[derive(Debug, derive_more::Display, Clone, Eq, PartialEq, Hash)]
+pub struct IdentA(pub data::Symbol);
+
+impl IdentA {
+    /// Returns the number of elements in the array.
+    pub fn size(&self) -> usize {
+        self.0
+            .array_size()
+            .expect("Array identifier refers to a symbol that is not an array.")
+    }
+}
+
+impl From<Identifier> for IdentA {
+    fn from(id: Identifier) -> Self {
+        IdentA(id.symbol)
+    }
+}
+
 /// Represents an int type LValue
 /// that can either be a temporary
 /// or an int identifier.
@@ -332,9 +351,16 @@ impl From<data::DataType> for ResultType {
                 panic!("STRING type is not a valid result of any 3AC operations.")
             }
             data::DataType::Num(t) => match t {
-                NumType::Int => ResultType::Int,
+                // BOOL values are stored as INTs
+                NumType::Int | NumType::Bool => ResultType::Int,
                 NumType::Float => ResultType::Float,
             },
+            data::DataType::Array(_) => {
+                panic!("ARRAY type is not a valid result of any 3AC operations.")
+            }
+            data::DataType::Record => {
+                panic!("RECORD type is not a valid result of any 3AC operations.")
+            }
         }
     }
 }
@@ -1,71 +1,110 @@
-use crate::register_alloc::types::RegisterId;
 use crate::three_addr_code_ir::{
-    FunctionIdent, IdentF, IdentI, IdentS, LValueF, LValueI, Label, RValueF, RValueI, ResultType,
+    FunctionIdent, IdentA, IdentF, IdentI, IdentS, LValueF, LValueI, Label, RValueF, RValueI,
     TempF, TempI,
 };
 
 #[derive(Debug, Clone, derive_more::Display, PartialEq)]
 pub enum ThreeAddressCode {
-    #[display(fmt = "ADDI {} {} {}", lhs, rhs, temp_result)]
+    #[display(fmt = "ADDI {} {} {}", lhs, rhs, result)]
     AddI {
         lhs: LValueI,
         rhs: LValueI,
-        temp_result: TempI,
+        result: LValueI,
     },
-    #[display(fmt = "SUBI {} {} {}", lhs, rhs, temp_result)]
+    #[display(fmt = "SUBI {} {} {}", lhs, rhs, result)]
     SubI {
         lhs: LValueI,
         rhs: LValueI,
-        temp_result: TempI,
+        result: LValueI,
     },
-    #[display(fmt = "MULTI {} {} {}", lhs, rhs, temp_result)]
+    #[display(fmt = "MULTI {} {} {}", lhs, rhs, result)]
     MulI {
         lhs: LValueI,
         rhs: LValueI,
-        temp_result: TempI,
+        result: LValueI,
     },
-    #[display(fmt = "DIVI {} {} {}", lhs, rhs, temp_result)]
+    #[display(fmt = "DIVI {} {} {}", lhs, rhs, result)]
     DivI {
         lhs: LValueI,
         rhs: LValueI,
-        temp_result: TempI,
+        result: LValueI,
+    },
+    #[display(fmt = "MODI {} {} {}", lhs, rhs, result)]
+    ModI {
+        lhs: LValueI,
+        rhs: LValueI,
+        result: LValueI,
     },
     #[display(fmt = "STOREI {} {}", rhs, lhs)]
     StoreI { lhs: LValueI, rhs: RValueI },
     #[display(fmt = "READI {}", identifier)]
     ReadI { identifier: IdentI },
-    #[display(fmt = "WRITEI {}", identifier)]
-    WriteI { identifier: IdentI },
-    #[display(fmt = "ADDF {} {} {}", lhs, rhs, temp_result)]
+    #[display(fmt = "WRITEI {}", value)]
+    WriteI { value: LValueI },
+    #[display(fmt = "LOADI {}[{}] {}", array, index, temp_result)]
+    LoadElementI {
+        array: IdentA,
+        index: LValueI,
+        temp_result: TempI,
+    },
+    #[display(fmt = "STOREI {} {}[{}]", value, array, index)]
+    StoreElementI {
+        array: IdentA,
+        index: LValueI,
+        value: LValueI,
+    },
+    #[display(fmt = "ADDF {} {} {}", lhs, rhs, result)]
     AddF {
         lhs: LValueF,
         rhs: LValueF,
-        temp_result: TempF,
+        result: LValueF,
     },
-    #[display(fmt = "SUBF {} {} {}", lhs, rhs, temp_result)]
+    #[display(fmt = "SUBF {} {} {}", lhs, rhs, result)]
     SubF {
         lhs: LValueF,
         rhs: LValueF,
-        temp_result: TempF,
+        result: LValueF,
     },
-    #[display(fmt = "MULTF {} {} {}", lhs, rhs, temp_result)]
+    #[display(fmt = "MULTF {} {} {}", lhs, rhs, result)]
     MulF {
         lhs: LValueF,
         rhs: LValueF,
-        temp_result: TempF,
+        result: LValueF,
     },
-    #[display(fmt = "DIVF {} {} {}", lhs, rhs, temp_result)]
+    #[display(fmt = "DIVF {} {} {}", lhs, rhs, result)]
     DivF {
         lhs: LValueF,
         rhs: LValueF,
-        temp_result: TempF,
+        result: LValueF,
     },
     #[display(fmt = "STOREF {} {}", rhs, lhs)]
     StoreF { lhs: LValueF, rhs: RValueF },
     #[display(fmt = "READF {}", identifier)]
     ReadF { identifier: IdentF },
-    #[display(fmt = "WRITEF {}", identifier)]
-    WriteF { identifier: IdentF },
+    #[display(fmt = "WRITEF {}", value)]
+    WriteF { value: LValueF },
+    #[display(fmt = "ITOF {} {}", operand, temp_result)]
+    IntToFloat {
+        operand: LValueI,
+        temp_result: TempF,
+    },
+    #[display(fmt = "FTOI {} {}", operand, temp_result)]
+    FloatToInt {
+        operand: LValueF,
+        temp_result: TempI,
+    },
+    #[display(fmt = "LOADF {}[{}] {}", array, index, temp_result)]
+    LoadElementF {
+        array: IdentA,
+        index: LValueI,
+        temp_result: TempF,
+    },
+    #[display(fmt = "STOREF {} {}[{}]", value, array, index)]
+    StoreElementF {
+        array: IdentA,
+        index: LValueI,
+        value: LValueF,
+    },
     #[display(fmt = "WRITES {}", identifier)]
     WriteS { identifier: IdentS },
     #[display(fmt = "LABEL {}", _0)]
@@ -232,7 +271,8 @@ impl ThreeAddressCode {
 pub mod visit {
     use crate::ast::ast_node::visit::Visitor;
     use crate::ast::ast_node::{
-        AddOp, Assignment, AstNode, CmpOp, Condition, Expr, Item, MulOp, Stmt,
+        AddOp, Assignment, AstNode, CmpOp, CompoundOp, Condition, ConditionKind, Expr, ExprKind,
+        Identifier, Item, MulOp, Stmt, StmtKind,
     };
     use crate::symbol_table::symbol::data::DataType;
     use crate::symbol_table::symbol::function::ReturnType;
@@ -243,10 +283,27 @@ pub mod visit {
     };
     use crate::three_addr_code_ir::{
         reset_temp_counter, FunctionIdent, IdentF, IdentI, LValue, LValueF, LValueI, Label,
-        ResultType, TempF, TempI,
+        RValueF, RValueI, ResultType, TempF, TempI,
     };
+    use std::cell::RefCell;
+    use std::rc::Rc;
     use typed_builder::TypedBuilder;
 
+    thread_local! {
+        /// Jump targets of the loops enclosing the
+        /// statement currently being visited, with
+        /// the innermost loop at the top.
+        static LOOP_LABELS: RefCell<Vec<LoopLabels>> = const { RefCell::new(Vec::new()) };
+    }
+
+    /// Jump targets of `CONTINUE` and `BREAK`
+    /// statements in the body of a loop.
+    #[derive(Debug, Copy, Clone)]
+    struct LoopLabels {
+        continue_label: Label,
+        break_label: Label,
+    }
+
     #[derive(Debug, Clone, TypedBuilder)]
     #[builder(field_defaults(default, setter(strip_option)))]
     pub struct CodeObject {
@@ -272,7 +329,7 @@ pub mod visit {
     impl ThreeAddressCodeVisitor {
         pub fn walk_ast(&mut self, ast: AstNode) -> CodeObject {
             match ast {
-                AstNode::Stmt(stmt) => self.visit_statement(stmt),
+                AstNode::Stmt(stmt) => self.visit_statement(*stmt),
                 AstNode::Expr(expr) => self.visit_expression(expr),
                 AstNode::Item(item) => self.visit_item(item),
             }
@@ -288,7 +345,7 @@ pub mod visit {
         fn visit_item(&mut self, item: Item) -> CodeObject {
             match item {
                 // TODO [unit tests]: Implement unit tests for 3AC code gen for functions
-                Item::Function { symbol, body } => {
+                Item::Function { symbol, body, .. } => {
                     reset_temp_counter();
                     let mut code_sequence = vec![];
                     code_sequence.push(ThreeAddressCode::FunctionLabel(FunctionIdent(
@@ -301,19 +358,34 @@ pub mod visit {
                         .collect();
                     code_sequence.append(&mut func_body);
 
+                    // Insert the function epilogue, unless the
+                    // function body already ends with a return.
+                    if !code_sequence
+                        .last()
+                        .is_some_and(ThreeAddressCode::is_return)
+                    {
+                        code_sequence.push(ThreeAddressCode::Ret);
+                    }
+
                     CodeObject::builder().code_sequence(code_sequence).build()
                 }
             }
         }
 
         fn visit_statement(&mut self, stmt: Stmt) -> CodeObject {
-            match stmt {
-                Stmt::Read(identifiers) => {
+            match stmt.kind {
+                StmtKind::Read(identifiers) => {
                     let code_sequence = identifiers
                         .into_iter()
                         .map(|identifier| match identifier.data_type() {
                             DataType::String => {
-                                panic!("Unsupported operation: cannot READ into string identifier!")
+                                panic!("Invalid AST: Cannot READ into string identifier!")
+                            }
+                            DataType::Array(_) => {
+                                panic!("Invalid AST: Cannot READ into array identifier!")
+                            }
+                            DataType::Record => {
+                                panic!("Invalid AST: Cannot READ into RECORD identifier!")
                             }
                             DataType::Num(num_type) => match num_type {
                                 NumType::Int => ThreeAddressCode::ReadI {
@@ -322,34 +394,46 @@ pub mod visit {
                                 NumType::Float => ThreeAddressCode::ReadF {
                                     identifier: identifier.into(),
                                 },
+                                NumType::Bool => {
+                                    panic!("Invalid AST: Cannot READ into BOOL identifier!")
+                                }
                             },
                         })
                         .collect();
 
                     CodeObject::builder().code_sequence(code_sequence).build()
                 }
-                Stmt::Write(identifiers) => {
-                    let code_sequence = identifiers
-                        .into_iter()
-                        .map(|identifier| match identifier.data_type() {
-                            DataType::String => ThreeAddressCode::WriteS {
-                                identifier: identifier.into(),
-                            },
-                            DataType::Num(num_type) => match num_type {
-                                NumType::Int => ThreeAddressCode::WriteI {
-                                    identifier: identifier.into(),
-                                },
-                                NumType::Float => ThreeAddressCode::WriteF {
+                StmtKind::Write(values) => {
+                    let mut code_sequence = vec![];
+
+                    for value in values {
+                        match value {
+                            Expr {
+                                kind: ExprKind::Id(identifier),
+                                ..
+                            } if identifier.data_type() == DataType::String => {
+                                code_sequence.push(ThreeAddressCode::WriteS {
                                     identifier: identifier.into(),
-                                },
-                            },
-                        })
-                        .collect();
+                                });
+                            }
+                            expr => {
+                                let expr = self.visit_expression(expr);
+                                code_sequence.extend(expr.code_sequence);
+
+                                // The result of a `CodeObject` returned
+                                // by an expression should never be `None`.
+                                code_sequence.push(match expr.result.unwrap() {
+                                    LValue::LValueI(value) => ThreeAddressCode::WriteI { value },
+                                    LValue::LValueF(value) => ThreeAddressCode::WriteF { value },
+                                });
+                            }
+                        }
+                    }
 
                     CodeObject::builder().code_sequence(code_sequence).build()
                 }
-                Stmt::Assign(assignment) => self.visit_assignment(assignment),
-                Stmt::If {
+                StmtKind::Assign(assignment) => self.visit_assignment(assignment),
+                StmtKind::If {
                     condition,
                     then_block,
                     else_block,
@@ -388,7 +472,7 @@ pub mod visit {
 
                     CodeObject::builder().code_sequence(code_sequence).build()
                 }
-                Stmt::For {
+                StmtKind::For {
                     init,
                     condition,
                     incr,
@@ -402,7 +486,7 @@ pub mod visit {
                     code_sequence.push(ThreeAddressCode::Label(loop_start_label));
 
                     // Generate loop condition 3AC
-                    let mut condition = self.visit_condition(condition);
+                    let mut condition = self.visit_condition(Box::into_inner(condition));
                     code_sequence.append(&mut condition.code_sequence);
                     // Unwrapping is safe here as the `jump_to` field
                     // of the returned `CodeObject`, from visiting a `Condition`
@@ -416,9 +500,13 @@ pub mod visit {
                     });
 
                     // Add loop statements 3AC
-                    body.into_iter().for_each(|stmt| {
-                        code_sequence.append(&mut self.visit_statement(stmt).code_sequence);
-                    });
+                    code_sequence.append(&mut self.visit_loop_body(
+                        body,
+                        LoopLabels {
+                            continue_label: loop_incr_label,
+                            break_label: loop_break_label,
+                        },
+                    ));
 
                     // Add loop incr 3AC
                     code_sequence.push(ThreeAddressCode::Label(loop_incr_label));
@@ -430,20 +518,122 @@ pub mod visit {
 
                     CodeObject::builder().code_sequence(code_sequence).build()
                 }
-                Stmt::Return(assignment) => {
-                    let mut code_sequence = self.visit_assignment(assignment).code_sequence;
+                StmtKind::DoWhile { body, condition } => {
+                    let loop_start_label = Label::new();
+                    let mut code_sequence = vec![ThreeAddressCode::Label(loop_start_label)];
+
+                    // Generate loop condition 3AC. The condition branches
+                    // out of the loop when it evaluates to false, otherwise
+                    // control falls through to the jump back to the loop start.
+                    // The condition is generated ahead of the loop statements
+                    // as they need its labels as `BREAK` and `CONTINUE` targets.
+                    let mut condition = self.visit_condition(condition);
+                    // Unwrapping is safe here as the `jump_to` field
+                    // of the returned `CodeObject`, from visiting a `Condition`
+                    // is guaranteed to be set.
+                    let loop_break_label = condition.jump_to.unwrap();
+                    let loop_cond_label = Label::new();
+
+                    // Add loop statements 3AC
+                    code_sequence.append(&mut self.visit_loop_body(
+                        body,
+                        LoopLabels {
+                            continue_label: loop_cond_label,
+                            break_label: loop_break_label,
+                        },
+                    ));
+
+                    code_sequence.push(ThreeAddressCode::Label(loop_cond_label));
+                    code_sequence.append(&mut condition.code_sequence);
+                    code_sequence.push(ThreeAddressCode::Jump(loop_start_label));
+
+                    // loop break-out label
+                    code_sequence.push(ThreeAddressCode::Label(loop_break_label));
+
+                    CodeObject::builder().code_sequence(code_sequence).build()
+                }
+                StmtKind::Case {
+                    selector,
+                    arms,
+                    default_block,
+                } => {
+                    let selector = self.visit_expression(selector);
+                    let mut code_sequence = selector.code_sequence;
+                    // The result of a `CodeObject` returned
+                    // by an expression should never be `None`.
+                    let selector = match selector.result.unwrap() {
+                        LValue::LValueI(selector) => selector,
+                        LValue::LValueF(_) => {
+                            panic!("Invalid AST: CASE selector must be an INT value!")
+                        }
+                    };
+
+                    let arms: Vec<(i32, Label, Vec<Stmt>)> = arms
+                        .into_iter()
+                        .map(|(value, block)| (value, Label::new(), block))
+                        .collect();
+                    let default_label = Label::new();
+                    let break_label = Label::new();
+
+                    let arm_labels: Vec<(i32, Label)> = arms
+                        .iter()
+                        .map(|(value, label, _)| (*value, *label))
+                        .collect();
+                    code_sequence.append(&mut case_dispatch_code(
+                        selector,
+                        arm_labels,
+                        default_label,
+                    ));
+
+                    // Arm blocks, each of which jumps past the rest
+                    for (_, label, block) in arms {
+                        code_sequence.push(ThreeAddressCode::Label(label));
+                        block.into_iter().for_each(|stmt| {
+                            code_sequence.append(&mut self.visit_statement(stmt).code_sequence);
+                        });
+                        code_sequence.push(Jump(break_label));
+                    }
+
+                    // `DEFAULT` block, which is last and falls through
+                    code_sequence.push(ThreeAddressCode::Label(default_label));
+                    default_block.into_iter().for_each(|stmt| {
+                        code_sequence.append(&mut self.visit_statement(stmt).code_sequence);
+                    });
+
+                    // case block break-out label
+                    code_sequence.push(ThreeAddressCode::Label(break_label));
+
+                    CodeObject::builder().code_sequence(code_sequence).build()
+                }
+                StmtKind::Break => {
+                    let loop_labels = innermost_loop_labels("BREAK");
+                    CodeObject::builder()
+                        .code_sequence(vec![Jump(loop_labels.break_label)])
+                        .build()
+                }
+                StmtKind::Continue => {
+                    let loop_labels = innermost_loop_labels("CONTINUE");
+                    CodeObject::builder()
+                        .code_sequence(vec![Jump(loop_labels.continue_label)])
+                        .build()
+                }
+                StmtKind::Call { func_symbol, args } => self.visit_call(func_symbol, args, true),
+                StmtKind::Return(assignment) => {
+                    let mut code_sequence = assignment
+                        .map(|assignment| self.visit_assignment(assignment).code_sequence)
+                        .unwrap_or_default();
                     code_sequence.push(ThreeAddressCode::Ret);
                     CodeObject::builder().code_sequence(code_sequence).build()
                 }
-                Stmt::None => {
+                StmtKind::None => {
                     panic!("Invalid AST: AST statement node contains statement variant `None`.")
                 }
             }
         }
 
         fn visit_expression(&mut self, expr: Expr) -> CodeObject {
-            match expr {
-                Expr::Id(identifier) => {
+            match expr.kind {
+                ExprKind::Id(identifier) => {
                     let result_type = identifier.data_type().into();
 
                     let result: LValue = match result_type {
@@ -453,7 +643,7 @@ pub mod visit {
 
                     CodeObject::builder().result(result).build()
                 }
-                Expr::IntLiteral(n) => {
+                ExprKind::IntLiteral(n) => {
                     let temp_result = TempI::new();
 
                     CodeObject::builder()
@@ -464,7 +654,18 @@ pub mod visit {
                         }])
                         .build()
                 }
-                Expr::FloatLiteral(n) => {
+                ExprKind::BoolLiteral(b) => {
+                    let temp_result = TempI::new();
+
+                    CodeObject::builder()
+                        .result(temp_result.into())
+                        .code_sequence(vec![ThreeAddressCode::StoreI {
+                            lhs: LValueI::Temp(temp_result),
+                            rhs: (b as i32).into(),
+                        }])
+                        .build()
+                }
+                ExprKind::FloatLiteral(n) => {
                     let temp_result = TempF::new();
 
                     CodeObject::builder()
@@ -475,14 +676,74 @@ pub mod visit {
                         }])
                         .build()
                 }
-                Expr::Add { op, lhs, rhs } => {
+                ExprKind::Neg(operand) => match Box::into_inner(operand) {
+                    // Negative literals fold to a single constant store
+                    Expr {
+                        kind: ExprKind::IntLiteral(n),
+                        ..
+                    } => self.visit_expression(Expr::new(ExprKind::IntLiteral(-n), expr.span)),
+                    Expr {
+                        kind: ExprKind::FloatLiteral(n),
+                        ..
+                    } => self.visit_expression(Expr::new(ExprKind::FloatLiteral(-n), expr.span)),
+                    expr => {
+                        let expr = self.visit_expression(expr);
+                        let (operand, mut code_sequence) =
+                            (expr.result.unwrap(), expr.code_sequence);
+
+                        // -x is lowered to 0 - x
+                        let (zero_store, curr_code, result_register) = match operand {
+                            LValue::LValueI(operand) => {
+                                let zero = TempI::new();
+                                let temp_result = TempI::new();
+                                (
+                                    ThreeAddressCode::StoreI {
+                                        lhs: LValueI::Temp(zero),
+                                        rhs: 0.into(),
+                                    },
+                                    ThreeAddressCode::SubI {
+                                        lhs: LValueI::Temp(zero),
+                                        rhs: operand,
+                                        result: LValueI::Temp(temp_result),
+                                    },
+                                    temp_result.into(),
+                                )
+                            }
+                            LValue::LValueF(operand) => {
+                                let zero = TempF::new();
+                                let temp_result = TempF::new();
+                                (
+                                    ThreeAddressCode::StoreF {
+                                        lhs: LValueF::Temp(zero),
+                                        rhs: 0.0.into(),
+                                    },
+                                    ThreeAddressCode::SubF {
+                                        lhs: LValueF::Temp(zero),
+                                        rhs: operand,
+                                        result: LValueF::Temp(temp_result),
+                                    },
+                                    temp_result.into(),
+                                )
+                            }
+                        };
+
+                        code_sequence.push(zero_store);
+                        code_sequence.push(curr_code);
+
+                        CodeObject::builder()
+                            .result(result_register)
+                            .code_sequence(code_sequence)
+                            .build()
+                    }
+                },
+                ExprKind::Add { op, lhs, rhs } => {
                     let lhs = self.visit_expression(Box::into_inner(lhs));
                     let rhs = self.visit_expression(Box::into_inner(rhs));
 
-                    let (curr_left_operand, mut left_code_seq) =
-                        (lhs.result.unwrap(), lhs.code_sequence);
-                    let (curr_right_operand, mut right_code_seq) =
-                        (rhs.result.unwrap(), rhs.code_sequence);
+                    let (
+                        (curr_left_operand, mut left_code_seq),
+                        (curr_right_operand, mut right_code_seq),
+                    ) = promote_operands(lhs, rhs);
 
                     let (curr_code, result_register) = match op {
                         AddOp::Add => match (curr_left_operand, curr_right_operand) {
@@ -492,7 +753,7 @@ pub mod visit {
                                     ThreeAddressCode::AddI {
                                         lhs: left.into(),
                                         rhs: right.into(),
-                                        temp_result,
+                                        result: LValueI::Temp(temp_result),
                                     },
                                     temp_result.into(),
                                 )
@@ -503,13 +764,13 @@ pub mod visit {
                                     ThreeAddressCode::AddF {
                                         lhs: left.into(),
                                         rhs: right.into(),
-                                        temp_result,
+                                        result: LValueF::Temp(temp_result),
                                     },
                                     temp_result.into(),
                                 )
                             }
                             (left, right) => panic!(
-                                "Unsupported operands for Add. Left: [{:?}], Right: [{:?}]",
+                                "Invalid AST: Unsupported operands for Add. Left: [{:?}], Right: [{:?}]",
                                 left.result_type(),
                                 right.result_type()
                             ),
@@ -521,7 +782,7 @@ pub mod visit {
                                     ThreeAddressCode::SubI {
                                         lhs: left.into(),
                                         rhs: right.into(),
-                                        temp_result,
+                                        result: LValueI::Temp(temp_result),
                                     },
                                     temp_result.into(),
                                 )
@@ -532,13 +793,13 @@ pub mod visit {
                                     ThreeAddressCode::SubF {
                                         lhs: left.into(),
                                         rhs: right.into(),
-                                        temp_result,
+                                        result: LValueF::Temp(temp_result),
                                     },
                                     temp_result.into(),
                                 )
                             }
                             (left, right) => panic!(
-                                "Unsupported operands for Sub. Left: [{:?}], Right: [{:?}]",
+                                "Invalid AST: Unsupported operands for Sub. Left: [{:?}], Right: [{:?}]",
                                 left.result_type(),
                                 right.result_type()
                             ),
@@ -553,14 +814,14 @@ pub mod visit {
                         .code_sequence(left_code_seq)
                         .build()
                 }
-                Expr::Mul { op, lhs, rhs } => {
+                ExprKind::Mul { op, lhs, rhs } => {
                     let lhs = self.visit_expression(Box::into_inner(lhs));
                     let rhs = self.visit_expression(Box::into_inner(rhs));
 
-                    let (curr_left_operand, mut left_code_seq) =
-                        (lhs.result.unwrap(), lhs.code_sequence);
-                    let (curr_right_operand, mut right_code_seq) =
-                        (rhs.result.unwrap(), rhs.code_sequence);
+                    let (
+                        (curr_left_operand, mut left_code_seq),
+                        (curr_right_operand, mut right_code_seq),
+                    ) = promote_operands(lhs, rhs);
 
                     let (curr_code, result_register) = match op {
                         MulOp::Mul => match (curr_left_operand, curr_right_operand) {
@@ -570,7 +831,7 @@ pub mod visit {
                                     ThreeAddressCode::MulI {
                                         lhs: left.into(),
                                         rhs: right.into(),
-                                        temp_result,
+                                        result: LValueI::Temp(temp_result),
                                     },
                                     temp_result.into(),
                                 )
@@ -581,13 +842,13 @@ pub mod visit {
                                     ThreeAddressCode::MulF {
                                         lhs: left.into(),
                                         rhs: right.into(),
-                                        temp_result,
+                                        result: LValueF::Temp(temp_result),
                                     },
                                     temp_result.into(),
                                 )
                             }
                             (left, right) => panic!(
-                                "Unsupported operands for Mul. Left: [{:?}], Right: [{:?}]",
+                                "Invalid AST: Unsupported operands for Mul. Left: [{:?}], Right: [{:?}]",
                                 left.result_type(),
                                 right.result_type()
                             ),
@@ -599,7 +860,7 @@ pub mod visit {
                                     ThreeAddressCode::DivI {
                                         lhs: left.into(),
                                         rhs: right.into(),
-                                        temp_result,
+                                        result: LValueI::Temp(temp_result),
                                     },
                                     temp_result.into(),
                                 )
@@ -610,13 +871,31 @@ pub mod visit {
                                     ThreeAddressCode::DivF {
                                         lhs: left.into(),
                                         rhs: right.into(),
-                                        temp_result,
+                                        result: LValueF::Temp(temp_result),
+                                    },
+                                    temp_result.into(),
+                                )
+                            }
+                            (left, right) => panic!(
+                                "Invalid AST: Unsupported operands for Div. Left: [{:?}], Right: [{:?}]",
+                                left.result_type(),
+                                right.result_type()
+                            ),
+                        },
+                        MulOp::Mod => match (curr_left_operand, curr_right_operand) {
+                            (LValue::LValueI(left), LValue::LValueI(right)) => {
+                                let temp_result = TempI::new();
+                                (
+                                    ThreeAddressCode::ModI {
+                                        lhs: left.into(),
+                                        rhs: right.into(),
+                                        result: LValueI::Temp(temp_result),
                                     },
                                     temp_result.into(),
                                 )
                             }
                             (left, right) => panic!(
-                                "Unsupported operands for Div. Left: [{:?}], Right: [{:?}]",
+                                "Invalid AST: Unsupported operands for Mod. Left: [{:?}], Right: [{:?}]",
                                 left.result_type(),
                                 right.result_type()
                             ),
@@ -631,90 +910,168 @@ pub mod visit {
                         .code_sequence(left_code_seq)
                         .build()
                 }
-                Expr::Call { func_symbol, args } => {
-                    let mut code_sequence = vec![];
-                    let return_type = func_symbol.return_type();
-
-                    // Generate instructions to evaluate all function
-                    // parameters and add them to the existing code sequence.
-                    // Store the temporaries containing the results of the function
-                    // parameter expressions to set up the stack in preparation
-                    // for the function call.
-                    let num_args = args.len();
-                    let mut push_arg_instrs = args
-                        .into_iter()
-                        .map(|expr| {
-                            let mut expr_code_obj = self.visit_expression(expr);
-                            code_sequence.append(&mut expr_code_obj.code_sequence);
-                            // The result of a `CodeObject` returned
-                            // by an expression should never be `None`.
-                            // An expression should always evaluate to
-                            // a result with a strong type.
-                            expr_code_obj.result.unwrap()
-                        })
-                        .map(|arg| match arg {
-                            LValue::LValueI(arg) => ThreeAddressCode::PushI(arg),
-                            LValue::LValueF(arg) => ThreeAddressCode::PushF(arg),
-                        })
-                        .collect();
+                ExprKind::Call { func_symbol, args } => self.visit_call(func_symbol, args, false),
+                ExprKind::Index { array, index } => {
+                    let (index, mut code_sequence) = self.visit_index(Box::into_inner(index));
 
-                    // If the function being called returns a value,
-                    // push empty slot for result of function.
-                    if return_type != function::ReturnType::Void {
-                        code_sequence.push(ThreeAddressCode::PushEmpty);
-                    }
+                    let (curr_code, result_register) = load_element(array, index);
+                    code_sequence.push(curr_code);
 
-                    code_sequence.append(&mut push_arg_instrs);
+                    CodeObject::builder()
+                        .result(result_register)
+                        .code_sequence(code_sequence)
+                        .build()
+                }
+                ExprKind::Cast { num_type, expr } => {
+                    let expr = self.visit_expression(Box::into_inner(expr));
+                    let (operand, mut code_sequence) = (expr.result.unwrap(), expr.code_sequence);
+
+                    let result: LValue = match num_type {
+                        NumType::Int => narrow_to_int(operand, &mut code_sequence).into(),
+                        NumType::Float => widen_to_float(operand, &mut code_sequence).into(),
+                        NumType::Bool => panic!("Invalid AST: Cannot cast to BOOL."),
+                    };
 
-                    // Jump to target - current pc is pushed onto the stack as part of this instruction.
-                    // The pc pushed onto the stack should be popped off in the callee code.
-                    code_sequence.push(ThreeAddressCode::Jsr(FunctionIdent(func_symbol)));
+                    CodeObject::builder()
+                        .result(result)
+                        .code_sequence(code_sequence)
+                        .build()
+                }
+                ExprKind::Condition(condition) => {
+                    // The value of the condition is only materialized
+                    // as 1 or 0 here, where it is used as a value.
+                    let false_label = Label::new();
+                    let end_label = Label::new();
+                    let temp_result = TempI::new();
 
-                    // Pop all the function parameters
-                    (0..num_args).for_each(|_| code_sequence.push(ThreeAddressCode::PopEmpty));
+                    let mut code_sequence =
+                        self.visit_condition_branch(Box::into_inner(condition), false_label, false);
+                    code_sequence.push(ThreeAddressCode::StoreI {
+                        lhs: LValueI::Temp(temp_result),
+                        rhs: 1.into(),
+                    });
+                    code_sequence.push(Jump(end_label));
+                    code_sequence.push(ThreeAddressCode::Label(false_label));
+                    code_sequence.push(ThreeAddressCode::StoreI {
+                        lhs: LValueI::Temp(temp_result),
+                        rhs: 0.into(),
+                    });
+                    code_sequence.push(ThreeAddressCode::Label(end_label));
 
-                    // If the function being called returns a value,
-                    // pop the function call result and store it in a temporary.
-                    let result_register = match return_type {
-                        ReturnType::Num(num_type) => match num_type {
-                            NumType::Int => {
-                                let result_register = TempI::new();
-                                code_sequence
-                                    .push(ThreeAddressCode::PopI(LValueI::Temp(result_register)));
-                                Some(result_register.into())
-                            }
-                            NumType::Float => {
-                                let result_register = TempF::new();
-                                code_sequence
-                                    .push(ThreeAddressCode::PopF(LValueF::Temp(result_register)));
-                                Some(result_register.into())
-                            }
-                        },
-                        ReturnType::Void => None,
+                    CodeObject::builder()
+                        .result(temp_result.into())
+                        .code_sequence(code_sequence)
+                        .build()
+                }
+                ExprKind::Conditional {
+                    condition,
+                    then_expr,
+                    else_expr,
+                } => {
+                    // Both branches write the chosen value to the
+                    // same temporary, which is read after they join.
+                    let else_label = Label::new();
+                    let end_label = Label::new();
+
+                    let mut code_sequence =
+                        self.visit_condition_branch(Box::into_inner(condition), else_label, false);
+
+                    let then_expr = self.visit_expression(Box::into_inner(then_expr));
+                    let else_expr = self.visit_expression(Box::into_inner(else_expr));
+                    let ((then_value, mut then_code_seq), (else_value, mut else_code_seq)) =
+                        promote_operands(then_expr, else_expr);
+
+                    let (then_store, else_store, result_register) = match (then_value, else_value)
+                    {
+                        (LValue::LValueI(then_value), LValue::LValueI(else_value)) => {
+                            let temp_result = TempI::new();
+                            (
+                                ThreeAddressCode::StoreI {
+                                    lhs: LValueI::Temp(temp_result),
+                                    rhs: then_value.into(),
+                                },
+                                ThreeAddressCode::StoreI {
+                                    lhs: LValueI::Temp(temp_result),
+                                    rhs: else_value.into(),
+                                },
+                                temp_result.into(),
+                            )
+                        }
+                        (LValue::LValueF(then_value), LValue::LValueF(else_value)) => {
+                            let temp_result = TempF::new();
+                            (
+                                ThreeAddressCode::StoreF {
+                                    lhs: LValueF::Temp(temp_result),
+                                    rhs: then_value.into(),
+                                },
+                                ThreeAddressCode::StoreF {
+                                    lhs: LValueF::Temp(temp_result),
+                                    rhs: else_value.into(),
+                                },
+                                temp_result.into(),
+                            )
+                        }
+                        (then_value, else_value) => panic!(
+                            "Invalid AST: Unsupported values for a conditional expression. Then: [{:?}], Else: [{:?}]",
+                            then_value.result_type(),
+                            else_value.result_type()
+                        ),
                     };
 
-                    match return_type {
-                        ReturnType::Num(_) => CodeObject::builder()
-                            .result(result_register.unwrap())
-                            .code_sequence(code_sequence)
-                            .build(),
-                        ReturnType::Void => {
-                            CodeObject::builder().code_sequence(code_sequence).build()
-                        }
-                    }
+                    code_sequence.append(&mut then_code_seq);
+                    code_sequence.push(then_store);
+                    code_sequence.push(Jump(end_label));
+                    code_sequence.push(ThreeAddressCode::Label(else_label));
+                    code_sequence.append(&mut else_code_seq);
+                    code_sequence.push(else_store);
+                    code_sequence.push(ThreeAddressCode::Label(end_label));
+
+                    CodeObject::builder()
+                        .result(result_register)
+                        .code_sequence(code_sequence)
+                        .build()
                 }
-                Expr::None => {
+                ExprKind::None => {
                     panic!("Invalid AST: AST expression node contains expression variant `None`.")
                 }
             }
         }
 
         fn visit_assignment(&mut self, assigment: Assignment) -> CodeObject {
-            let Assignment { lhs, rhs } = assigment;
+            let Assignment {
+                lhs,
+                index,
+                op,
+                rhs,
+                ..
+            } = assigment;
+
+            // The index of an array element is
+            // evaluated ahead of the assigned value.
+            let (index, mut code_sequence) = match index {
+                Some(index) => {
+                    let (index, code_sequence) = self.visit_index(Box::into_inner(index));
+                    (Some(index), code_sequence)
+                }
+                None => (None, vec![]),
+            };
+
+            // A compound assignment to an array element operates
+            // on the current value of the element, which is
+            // likewise loaded ahead of the assigned value.
+            let element = match (op, &index) {
+                (Some(_), Some(index)) => {
+                    let (load_code, element) = load_element(lhs.clone(), index.clone());
+                    code_sequence.push(load_code);
+
+                    Some(element)
+                }
+                _ => None,
+            };
 
             let rhs = self.visit_expression(rhs);
 
-            let (result, mut code_sequence) = (
+            let (mut result, mut rhs_code_sequence) = (
                 // The result of a `CodeObject` returned
                 // by an expression should never be `None`.
                 // An expression should always evaluate to
@@ -722,22 +1079,95 @@ pub mod visit {
                 rhs.result.unwrap(),
                 rhs.code_sequence,
             );
+            code_sequence.append(&mut rhs_code_sequence);
+
+            if let Some(op) = op {
+                // The op writes straight into a variable target. The
+                // new value of an element is computed into a temporary
+                // and then stored like any other assigned value.
+                let (current, target) = match element {
+                    Some(element) => {
+                        let target = match element.result_type() {
+                            ResultType::Int => TempI::new().into(),
+                            ResultType::Float => TempF::new().into(),
+                        };
+                        (element, target)
+                    }
+                    None => {
+                        let target: LValue = match lhs.data_type() {
+                            DataType::Num(NumType::Int) => {
+                                LValueI::Id(IdentI(lhs.symbol.clone())).into()
+                            }
+                            DataType::Num(NumType::Float) => {
+                                LValueF::Id(IdentF(lhs.symbol.clone())).into()
+                            }
+                            data_type => {
+                                panic!("Invalid AST: Cannot apply {:?} to {:?}!", op, data_type)
+                            }
+                        };
+                        (target.clone(), target)
+                    }
+                };
+
+                let op_code =
+                    compound_op_code(op, current, result, target.clone(), &mut code_sequence);
+                code_sequence.push(op_code);
+
+                if index.is_none() {
+                    return CodeObject::builder().code_sequence(code_sequence).build();
+                }
+
+                result = target;
+            }
+
+            if let Some(index) = index {
+                let assign_code = match (lhs.data_type(), result) {
+                    (DataType::Array(NumType::Int), LValue::LValueI(value)) => {
+                        ThreeAddressCode::StoreElementI {
+                            array: lhs.into(),
+                            index,
+                            value,
+                        }
+                    }
+                    (DataType::Array(NumType::Float), value) => ThreeAddressCode::StoreElementF {
+                        array: lhs.into(),
+                        index,
+                        value: widen_to_float(value, &mut code_sequence),
+                    },
+                    (data_type, result) => panic!(
+                        "Invalid AST: Cannot assign {:?} to an element of {:?}",
+                        result.result_type(),
+                        data_type
+                    ),
+                };
+
+                code_sequence.push(assign_code);
+
+                return CodeObject::builder().code_sequence(code_sequence).build();
+            }
 
             let assign_code = match lhs.data_type() {
                 DataType::String => {
-                    panic!("Unsupported operation: Cannot ASSIGN to a string identifier!")
+                    panic!("Invalid AST: Cannot ASSIGN to a string identifier!")
+                }
+                DataType::Array(_) => {
+                    panic!("Invalid AST: Cannot ASSIGN to an array identifier without an index!")
+                }
+                DataType::Record => {
+                    panic!("Invalid AST: Cannot ASSIGN to a RECORD identifier!")
                 }
                 DataType::Num(num_type) => match (num_type, result) {
-                    (NumType::Int, LValue::LValueI(result)) => ThreeAddressCode::StoreI {
+                    (NumType::Int, LValue::LValueI(result))
+                    | (NumType::Bool, LValue::LValueI(result)) => ThreeAddressCode::StoreI {
                         lhs: LValueI::Id(IdentI(lhs.symbol)),
                         rhs: result.into(),
                     },
-                    (NumType::Float, LValue::LValueF(result)) => ThreeAddressCode::StoreF {
+                    (NumType::Float, result) => ThreeAddressCode::StoreF {
                         lhs: LValueF::Id(IdentF(lhs.symbol)),
-                        rhs: result.into(),
+                        rhs: widen_to_float(result, &mut code_sequence).into(),
                     },
                     (_, result) => panic!(
-                        "Unsupported assignment. Cannot assign {:?} to {:?}",
+                        "Invalid AST: Cannot assign {:?} to {:?}",
                         result.result_type(),
                         num_type
                     ),
@@ -750,140 +1180,726 @@ pub mod visit {
         }
 
         fn visit_condition(&mut self, condition: Condition) -> CodeObject {
-            let Condition { cmp_op, lhs, rhs } = condition;
-
-            let lhs = self.visit_expression(lhs);
-            let rhs = self.visit_expression(rhs);
-
-            let (curr_left_operand, mut left_code_seq) = (lhs.result.unwrap(), lhs.code_sequence);
-            let (curr_right_operand, mut right_code_seq) = (rhs.result.unwrap(), rhs.code_sequence);
-
+            // The code generated for a condition falls through when the
+            // condition evaluates to true and jumps to `else_label` otherwise.
             let else_label = Label::new();
-
-            let curr_code =
-                match cmp_op {
-                    CmpOp::Lt => match (curr_left_operand, curr_right_operand) {
-                        (LValue::LValueI(left), LValue::LValueI(right)) => GteI {
-                            lhs: left.into(),
-                            rhs: right.into(),
-                            label: else_label,
-                        },
-                        (LValue::LValueF(left), LValue::LValueF(right)) => GteF {
-                            lhs: left.into(),
-                            rhs: right.into(),
-                            label: else_label,
-                        },
-                        (left, right) => panic!(
-                        "Unsupported comparison operand combination. Left: [{:?}], Right: [{:?}]",
-                        left.result_type(), right.result_type()
-                    ),
-                    },
-                    CmpOp::Gt => match (curr_left_operand, curr_right_operand) {
-                        (LValue::LValueI(left), LValue::LValueI(right)) => LteI {
-                            lhs: left.into(),
-                            rhs: right.into(),
-                            label: else_label,
-                        },
-                        (LValue::LValueF(left), LValue::LValueF(right)) => LteF {
-                            lhs: left.into(),
-                            rhs: right.into(),
-                            label: else_label,
-                        },
-                        (left, right) => panic!(
-                        "Unsupported comparison operand combination. Left: [{:?}], Right: [{:?}]",
-                        left.result_type(), right.result_type()
-                    ),
-                    },
-                    CmpOp::Eq => match (curr_left_operand, curr_right_operand) {
-                        (LValue::LValueI(left), LValue::LValueI(right)) => NeI {
-                            lhs: left.into(),
-                            rhs: right.into(),
-                            label: else_label,
-                        },
-                        (LValue::LValueF(left), LValue::LValueF(right)) => NeF {
-                            lhs: left.into(),
-                            rhs: right.into(),
-                            label: else_label,
-                        },
-                        (left, right) => panic!(
-                        "Unsupported comparison operand combination. Left: [{:?}], Right: [{:?}]",
-                        left.result_type(), right.result_type()
-                    ),
-                    },
-                    CmpOp::Ne => match (curr_left_operand, curr_right_operand) {
-                        (LValue::LValueI(left), LValue::LValueI(right)) => EqI {
-                            lhs: left.into(),
-                            rhs: right.into(),
-                            label: else_label,
-                        },
-                        (LValue::LValueF(left), LValue::LValueF(right)) => EqF {
-                            lhs: left.into(),
-                            rhs: right.into(),
-                            label: else_label,
-                        },
-                        (left, right) => panic!(
-                        "Unsupported comparison operand combination. Left: [{:?}], Right: [{:?}]",
-                        left.result_type(), right.result_type()
-                    ),
-                    },
-                    CmpOp::Lte => match (curr_left_operand, curr_right_operand) {
-                        (LValue::LValueI(left), LValue::LValueI(right)) => GtI {
-                            lhs: left.into(),
-                            rhs: right.into(),
-                            label: else_label,
-                        },
-                        (LValue::LValueF(left), LValue::LValueF(right)) => GtF {
-                            lhs: left.into(),
-                            rhs: right.into(),
-                            label: else_label,
-                        },
-                        (left, right) => panic!(
-                        "Unsupported comparison operand combination. Left: [{:?}], Right: [{:?}]",
-                        left.result_type(), right.result_type()
-                    ),
-                    },
-                    CmpOp::Gte => match (curr_left_operand, curr_right_operand) {
-                        (LValue::LValueI(left), LValue::LValueI(right)) => LtI {
-                            lhs: left.into(),
-                            rhs: right.into(),
-                            label: else_label,
-                        },
-                        (LValue::LValueF(left), LValue::LValueF(right)) => LtF {
-                            lhs: left.into(),
-                            rhs: right.into(),
-                            label: else_label,
-                        },
-                        (left, right) => panic!(
-                        "Unsupported comparison operand combination. Left: [{:?}], Right: [{:?}]",
-                        left.result_type(), right.result_type()
-                    ),
-                    },
-                };
-
-            left_code_seq.append(&mut right_code_seq);
-            left_code_seq.push(curr_code);
+            let code_sequence = self.visit_condition_branch(condition, else_label, false);
 
             CodeObject::builder()
                 .jump_to(else_label)
-                .code_sequence(left_code_seq)
+                .code_sequence(code_sequence)
                 .build()
         }
     }
-}
 
-#[cfg(test)]
-mod test {
-    use crate::ast::ast_node::{AddOp, AstNode, CmpOp, Condition, Expr, Identifier, MulOp};
-    use crate::three_addr_code_ir::three_address_code::visit::ThreeAddressCodeVisitor;
-    use crate::three_addr_code_ir::{LValue, ResultType};
+    impl ThreeAddressCodeVisitor {
+        /// Generates 3AC for the index expression of an array
+        /// element, which must evaluate to an int.
+        fn visit_index(&mut self, index: Expr) -> (LValueI, Vec<ThreeAddressCode>) {
+            let index = self.visit_expression(index);
+
+            match index.result.unwrap() {
+                LValue::LValueI(result) => (result, index.code_sequence),
+                result => panic!(
+                    "Invalid AST: Cannot index an array with {:?}",
+                    result.result_type()
+                ),
+            }
+        }
+
+        /// Generates 3AC for the statements in the body of a
+        /// loop, with `loop_labels` as the targets of any
+        /// `BREAK` and `CONTINUE` statements in the body.
+        fn visit_loop_body(
+            &mut self,
+            body: Vec<Stmt>,
+            loop_labels: LoopLabels,
+        ) -> Vec<ThreeAddressCode> {
+            LOOP_LABELS.with(|labels| labels.borrow_mut().push(loop_labels));
+            let code_sequence = body
+                .into_iter()
+                .flat_map(|stmt| self.visit_statement(stmt).code_sequence)
+                .collect();
+            LOOP_LABELS.with(|labels| labels.borrow_mut().pop());
+
+            code_sequence
+        }
+
+        /// Generates the calling sequence for a function call. Parameters
+        /// are pushed onto the stack, preceded by an empty slot for the
+        /// result if the function returns a value. If `discard_result` is
+        /// set, the result slot is popped off without storing it anywhere.
+        fn visit_call(
+            &mut self,
+            func_symbol: Rc<function::Symbol>,
+            args: Vec<Expr>,
+            discard_result: bool,
+        ) -> CodeObject {
+            let mut code_sequence = vec![];
+            let return_type = func_symbol.return_type();
+
+            // Generate instructions to evaluate all function
+            // parameters and add them to the existing code sequence.
+            // Store the temporaries containing the results of the function
+            // parameter expressions to set up the stack in preparation
+            // for the function call.
+            // INT arguments passed to FLOAT parameters are
+            // widened like in an assignment.
+            // RECORD arguments have been expanded into
+            // their fields, one per parameter stack slot.
+            // The variable passed as a VAR argument, along with
+            // the index of the element if it is an array element,
+            // is kept to copy the value of the parameter back to it.
+            let param_slots = func_symbol.param_slots();
+            let var_param_slots = func_symbol.var_param_slots();
+            let mut var_args = vec![];
+            let mut push_arg_instrs = args
+                .into_iter()
+                .enumerate()
+                .map(|(i, expr)| {
+                    let is_var = var_param_slots.get(i).copied().unwrap_or(false);
+                    let arg = match expr {
+                        Expr {
+                            kind: ExprKind::Index { array, index },
+                            ..
+                        } if is_var => {
+                            let (index, mut index_code) = self.visit_index(Box::into_inner(index));
+                            code_sequence.append(&mut index_code);
+
+                            // The call may update the variables the index
+                            // is computed from, so the element is copied
+                            // back to at the index computed ahead of it.
+                            let temp_index = TempI::new();
+                            code_sequence.push(ThreeAddressCode::StoreI {
+                                lhs: LValueI::Temp(temp_index),
+                                rhs: index.into(),
+                            });
+                            let index = LValueI::Temp(temp_index);
+
+                            let (load_code, element) = load_element(array.clone(), index.clone());
+                            code_sequence.push(load_code);
+                            var_args.push(Some((array, Some(index))));
+                            element
+                        }
+                        expr => {
+                            if let (
+                                Expr {
+                                    kind: ExprKind::Id(identifier),
+                                    ..
+                                },
+                                true,
+                            ) = (&expr, is_var)
+                            {
+                                var_args.push(Some((identifier.clone(), None)));
+                            } else {
+                                var_args.push(None);
+                            }
+
+                            let mut expr_code_obj = self.visit_expression(expr);
+                            code_sequence.append(&mut expr_code_obj.code_sequence);
+                            // The result of a `CodeObject` returned
+                            // by an expression should never be `None`.
+                            // An expression should always evaluate to
+                            // a result with a strong type.
+                            expr_code_obj.result.unwrap()
+                        }
+                    };
+
+                    match param_slots.get(i) {
+                        Some(NumType::Float) => widen_to_float(arg, &mut code_sequence).into(),
+                        _ => arg,
+                    }
+                })
+                .map(|arg| match arg {
+                    LValue::LValueI(arg) => ThreeAddressCode::PushI(arg),
+                    LValue::LValueF(arg) => ThreeAddressCode::PushF(arg),
+                })
+                .collect();
+
+            // If the function being called returns a value,
+            // push empty slot for result of function.
+            if return_type != function::ReturnType::Void {
+                code_sequence.push(ThreeAddressCode::PushEmpty);
+            }
+
+            code_sequence.append(&mut push_arg_instrs);
+
+            // Jump to target - current pc is pushed onto the stack as part of this instruction.
+            // The pc pushed onto the stack should be popped off in the callee code.
+            code_sequence.push(ThreeAddressCode::Jsr(FunctionIdent(func_symbol)));
+
+            // Pop all the function parameters, copying the values
+            // of VAR parameters back to the variables passed for them.
+            for var_arg in var_args.into_iter().rev() {
+                match var_arg {
+                    Some((target, index)) => {
+                        code_sequence.append(&mut copy_out_code(target, index))
+                    }
+                    None => code_sequence.push(ThreeAddressCode::PopEmpty),
+                }
+            }
+
+            // If the function being called returns a value,
+            // pop the function call result and store it in a temporary.
+            let result_register = match return_type {
+                ReturnType::Num(_) if discard_result => {
+                    code_sequence.push(ThreeAddressCode::PopEmpty);
+                    None
+                }
+                ReturnType::Num(num_type) => match num_type {
+                    NumType::Int | NumType::Bool => {
+                        let result_register = TempI::new();
+                        code_sequence.push(ThreeAddressCode::PopI(LValueI::Temp(result_register)));
+                        Some(result_register.into())
+                    }
+                    NumType::Float => {
+                        let result_register = TempF::new();
+                        code_sequence.push(ThreeAddressCode::PopF(LValueF::Temp(result_register)));
+                        Some(result_register.into())
+                    }
+                },
+                ReturnType::Void => None,
+            };
+
+            match result_register {
+                Some(result_register) => CodeObject::builder()
+                    .result(result_register)
+                    .code_sequence(code_sequence)
+                    .build(),
+                None => CodeObject::builder().code_sequence(code_sequence).build(),
+            }
+        }
+
+        /// Lowers a (possibly compound) condition to a chain of
+        /// compare-branch 3AC which jumps to `label` when the condition
+        /// evaluates to `jump_if` and falls through otherwise. Operands
+        /// of `AND` and `OR` are evaluated left to right and the right
+        /// operand is only evaluated when the left operand does not
+        /// already decide the result.
+        fn visit_condition_branch(
+            &mut self,
+            condition: Condition,
+            label: Label,
+            jump_if: bool,
+        ) -> Vec<ThreeAddressCode> {
+            match condition.kind {
+                ConditionKind::Cmp { cmp_op, lhs, rhs } => {
+                    let lhs = self.visit_expression(lhs);
+                    let rhs = self.visit_expression(rhs);
+
+                    let (
+                        (curr_left_operand, mut left_code_seq),
+                        (curr_right_operand, mut right_code_seq),
+                    ) = promote_operands(lhs, rhs);
+
+                    // To jump when the comparison is false we branch
+                    // on the negated comparison instead.
+                    let cmp_op = if jump_if { cmp_op } else { cmp_op.negated() };
+
+                    left_code_seq.append(&mut right_code_seq);
+                    left_code_seq.push(compare_and_branch(
+                        cmp_op,
+                        curr_left_operand,
+                        curr_right_operand,
+                        label,
+                    ));
+
+                    left_code_seq
+                }
+                // A constant needs no test at all.
+                ConditionKind::Value(Expr {
+                    kind: ExprKind::BoolLiteral(b),
+                    ..
+                }) if b == jump_if => vec![Jump(label)],
+                ConditionKind::Value(Expr {
+                    kind: ExprKind::BoolLiteral(_),
+                    ..
+                }) => vec![],
+                ConditionKind::Value(expr) => {
+                    let value = self.visit_expression(expr);
+                    let mut code_sequence = value.code_sequence;
+
+                    // BOOL values are either 0 or 1, so test
+                    // them for being anything other than 0.
+                    let zero = TempI::new();
+                    code_sequence.push(ThreeAddressCode::StoreI {
+                        lhs: LValueI::Temp(zero),
+                        rhs: 0.into(),
+                    });
+
+                    let cmp_op = if jump_if { CmpOp::Ne } else { CmpOp::Eq };
+                    code_sequence.push(compare_and_branch(
+                        cmp_op,
+                        value.result.unwrap(),
+                        zero.into(),
+                        label,
+                    ));
+
+                    code_sequence
+                }
+                ConditionKind::Not(condition) => {
+                    self.visit_condition_branch(Box::into_inner(condition), label, !jump_if)
+                }
+                ConditionKind::And(lhs, rhs) => {
+                    if jump_if {
+                        // Jump only if both operands are true - a false
+                        // lhs skips the evaluation of rhs altogether.
+                        let skip_label = Label::new();
+                        let mut code_sequence =
+                            self.visit_condition_branch(Box::into_inner(lhs), skip_label, false);
+                        code_sequence.append(&mut self.visit_condition_branch(
+                            Box::into_inner(rhs),
+                            label,
+                            true,
+                        ));
+                        code_sequence.push(ThreeAddressCode::Label(skip_label));
+                        code_sequence
+                    } else {
+                        // Jump as soon as either operand is false.
+                        let mut code_sequence =
+                            self.visit_condition_branch(Box::into_inner(lhs), label, false);
+                        code_sequence.append(&mut self.visit_condition_branch(
+                            Box::into_inner(rhs),
+                            label,
+                            false,
+                        ));
+                        code_sequence
+                    }
+                }
+                ConditionKind::Or(lhs, rhs) => {
+                    if jump_if {
+                        // Jump as soon as either operand is true.
+                        let mut code_sequence =
+                            self.visit_condition_branch(Box::into_inner(lhs), label, true);
+                        code_sequence.append(&mut self.visit_condition_branch(
+                            Box::into_inner(rhs),
+                            label,
+                            true,
+                        ));
+                        code_sequence
+                    } else {
+                        // Jump only if both operands are false - a true
+                        // lhs skips the evaluation of rhs altogether.
+                        let skip_label = Label::new();
+                        let mut code_sequence =
+                            self.visit_condition_branch(Box::into_inner(lhs), skip_label, true);
+                        code_sequence.append(&mut self.visit_condition_branch(
+                            Box::into_inner(rhs),
+                            label,
+                            false,
+                        ));
+                        code_sequence.push(ThreeAddressCode::Label(skip_label));
+                        code_sequence
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns the jump targets of the innermost loop
+    /// enclosing the `stmt` statement being visited.
+    fn innermost_loop_labels(stmt: &str) -> LoopLabels {
+        LOOP_LABELS
+            .with(|labels| labels.borrow().last().copied())
+            .unwrap_or_else(|| panic!("Invalid AST: {} statement outside of a loop.", stmt))
+    }
+
+    /// Applies the numeric promotion rules to the operands of a
+    /// binary operation - when one operand is a FLOAT and the
+    /// other an INT, the INT operand is widened to a FLOAT.
+    fn promote_operands(
+        lhs: CodeObject,
+        rhs: CodeObject,
+    ) -> (
+        (LValue, Vec<ThreeAddressCode>),
+        (LValue, Vec<ThreeAddressCode>),
+    ) {
+        // The result of a `CodeObject` returned
+        // by an expression should never be `None`.
+        let (mut lhs, mut lhs_code_seq) = (lhs.result.unwrap(), lhs.code_sequence);
+        let (mut rhs, mut rhs_code_seq) = (rhs.result.unwrap(), rhs.code_sequence);
+
+        match (lhs.result_type(), rhs.result_type()) {
+            (ResultType::Int, ResultType::Float) => {
+                lhs = widen_to_float(lhs, &mut lhs_code_seq).into();
+            }
+            (ResultType::Float, ResultType::Int) => {
+                rhs = widen_to_float(rhs, &mut rhs_code_seq).into();
+            }
+            _ => (),
+        }
+
+        ((lhs, lhs_code_seq), (rhs, rhs_code_seq))
+    }
+
+    /// Generates the 3AC for the op of a compound assignment, which
+    /// combines `current`, the current value of the target, with `rhs`
+    /// and writes the result to `target`. `rhs`, computed by
+    /// `code_sequence`, is widened to a FLOAT for a FLOAT target.
+    fn compound_op_code(
+        op: CompoundOp,
+        current: LValue,
+        rhs: LValue,
+        target: LValue,
+        code_sequence: &mut Vec<ThreeAddressCode>,
+    ) -> ThreeAddressCode {
+        match (current, target) {
+            (LValue::LValueI(lhs), LValue::LValueI(result)) => {
+                let rhs = match rhs {
+                    LValue::LValueI(rhs) => rhs,
+                    rhs => panic!(
+                        "Invalid AST: Cannot assign {:?} to {:?}",
+                        rhs.result_type(),
+                        NumType::Int
+                    ),
+                };
+
+                match op {
+                    CompoundOp::Add => ThreeAddressCode::AddI { lhs, rhs, result },
+                    CompoundOp::Sub => ThreeAddressCode::SubI { lhs, rhs, result },
+                    CompoundOp::Mul => ThreeAddressCode::MulI { lhs, rhs, result },
+                    CompoundOp::Div => ThreeAddressCode::DivI { lhs, rhs, result },
+                }
+            }
+            (LValue::LValueF(lhs), LValue::LValueF(result)) => {
+                let rhs = widen_to_float(rhs, code_sequence);
+
+                match op {
+                    CompoundOp::Add => ThreeAddressCode::AddF { lhs, rhs, result },
+                    CompoundOp::Sub => ThreeAddressCode::SubF { lhs, rhs, result },
+                    CompoundOp::Mul => ThreeAddressCode::MulF { lhs, rhs, result },
+                    CompoundOp::Div => ThreeAddressCode::DivF { lhs, rhs, result },
+                }
+            }
+            (current, target) => panic!(
+                "Invalid AST: Mismatched types for the current value {:?} and the target {:?} of a compound assignment!",
+                current.result_type(),
+                target.result_type()
+            ),
+        }
+    }
+
+    /// Generates the 3AC to load the element of
+    /// `array` at `index` into a temporary.
+    fn load_element(array: Identifier, index: LValueI) -> (ThreeAddressCode, LValue) {
+        match array.data_type() {
+            DataType::Array(NumType::Int) => {
+                let temp_result = TempI::new();
+                (
+                    ThreeAddressCode::LoadElementI {
+                        array: array.into(),
+                        index,
+                        temp_result,
+                    },
+                    temp_result.into(),
+                )
+            }
+            DataType::Array(NumType::Float) => {
+                let temp_result = TempF::new();
+                (
+                    ThreeAddressCode::LoadElementF {
+                        array: array.into(),
+                        index,
+                        temp_result,
+                    },
+                    temp_result.into(),
+                )
+            }
+            data_type => panic!(
+                "Invalid AST: Cannot index an identifier of type {:?}.",
+                data_type
+            ),
+        }
+    }
+
+    /// Generates the 3AC to pop the value of a VAR parameter off
+    /// the stack after a call and copy it back to `target`, the
+    /// variable passed for it - or its element at `index` if given.
+    ///
+    /// VAR parameters are passed by copy-in/copy-out, as described
+    /// on `function::ParamType::Var`.
+    fn copy_out_code(target: Identifier, index: Option<LValueI>) -> Vec<ThreeAddressCode> {
+        match (target.data_type(), index) {
+            (DataType::Num(NumType::Int), None) | (DataType::Num(NumType::Bool), None) => {
+                let temp_result = TempI::new();
+                vec![
+                    ThreeAddressCode::PopI(LValueI::Temp(temp_result)),
+                    ThreeAddressCode::StoreI {
+                        lhs: LValueI::Id(IdentI(target.symbol)),
+                        rhs: LValueI::Temp(temp_result).into(),
+                    },
+                ]
+            }
+            (DataType::Num(NumType::Float), None) => {
+                let temp_result = TempF::new();
+                vec![
+                    ThreeAddressCode::PopF(LValueF::Temp(temp_result)),
+                    ThreeAddressCode::StoreF {
+                        lhs: LValueF::Id(IdentF(target.symbol)),
+                        rhs: LValueF::Temp(temp_result).into(),
+                    },
+                ]
+            }
+            (DataType::Array(NumType::Int), Some(index)) => {
+                let temp_result = TempI::new();
+                vec![
+                    ThreeAddressCode::PopI(LValueI::Temp(temp_result)),
+                    ThreeAddressCode::StoreElementI {
+                        array: target.into(),
+                        index,
+                        value: LValueI::Temp(temp_result),
+                    },
+                ]
+            }
+            (DataType::Array(NumType::Float), Some(index)) => {
+                let temp_result = TempF::new();
+                vec![
+                    ThreeAddressCode::PopF(LValueF::Temp(temp_result)),
+                    ThreeAddressCode::StoreElementF {
+                        array: target.into(),
+                        index,
+                        value: LValueF::Temp(temp_result),
+                    },
+                ]
+            }
+            (data_type, _) => panic!(
+                "Invalid AST: Cannot pass {:?} `{}` as a VAR argument.",
+                data_type, target.symbol
+            ),
+        }
+    }
+
+    /// Converts `operand`, computed by `code_sequence`, to a FLOAT.
+    /// An INT literal operand is converted at compile time.
+    fn widen_to_float(operand: LValue, code_sequence: &mut Vec<ThreeAddressCode>) -> LValueF {
+        let operand = match operand {
+            LValue::LValueI(operand) => operand,
+            LValue::LValueF(operand) => return operand,
+        };
+
+        let temp_result = TempF::new();
+        let conversion = match code_sequence.last() {
+            Some(ThreeAddressCode::StoreI {
+                lhs: lhs @ LValueI::Temp(_),
+                rhs: RValueI::RValue(n),
+            }) if *lhs == operand => {
+                let n = *n;
+                code_sequence.pop();
+                ThreeAddressCode::StoreF {
+                    lhs: LValueF::Temp(temp_result),
+                    rhs: (n as f64).into(),
+                }
+            }
+            _ => ThreeAddressCode::IntToFloat {
+                operand,
+                temp_result,
+            },
+        };
+        code_sequence.push(conversion);
+
+        LValueF::Temp(temp_result)
+    }
+
+    /// Converts `operand`, computed by `code_sequence`, to an INT by
+    /// truncating it towards zero. A FLOAT literal operand is
+    /// converted at compile time.
+    fn narrow_to_int(operand: LValue, code_sequence: &mut Vec<ThreeAddressCode>) -> LValueI {
+        let operand = match operand {
+            LValue::LValueI(operand) => return operand,
+            LValue::LValueF(operand) => operand,
+        };
+
+        let temp_result = TempI::new();
+        let conversion = match code_sequence.last() {
+            Some(ThreeAddressCode::StoreF {
+                lhs: lhs @ LValueF::Temp(_),
+                rhs: RValueF::RValue(n),
+            }) if *lhs == operand => {
+                let n = *n;
+                code_sequence.pop();
+                ThreeAddressCode::StoreI {
+                    lhs: LValueI::Temp(temp_result),
+                    rhs: (n as i32).into(),
+                }
+            }
+            _ => ThreeAddressCode::FloatToInt {
+                operand,
+                temp_result,
+            },
+        };
+        code_sequence.push(conversion);
+
+        LValueI::Temp(temp_result)
+    }
+
+    /// Generates the 3AC that jumps to the label of the arm whose
+    /// value equals `selector`, or to `default_label` if none does.
+    ///
+    /// Sparse values are compared one at a time, in order. Tiny has
+    /// no indirect jumps, so dense values - at least 4, spanning no
+    /// more than twice as many numbers - are instead dispatched on
+    /// like a jump table: a range check, followed by a binary search
+    /// over the sorted values.
+    fn case_dispatch_code(
+        selector: LValueI,
+        mut arms: Vec<(i32, Label)>,
+        default_label: Label,
+    ) -> Vec<ThreeAddressCode> {
+        let mut code_sequence = vec![];
+
+        let span = match (
+            arms.iter().map(|arm| arm.0).min(),
+            arms.iter().map(|arm| arm.0).max(),
+        ) {
+            (Some(min), Some(max)) => max as i64 - min as i64 + 1,
+            _ => 0,
+        };
+
+        if arms.len() < 4 || span > 2 * arms.len() as i64 {
+            case_compare_chain(&selector, &arms, default_label, &mut code_sequence);
+            return code_sequence;
+        }
+
+        arms.sort_unstable_by_key(|arm| arm.0);
+
+        // Range check
+        let (min, max) = (arms[0].0, arms[arms.len() - 1].0);
+        for (bound, cmp_op) in [(min, CmpOp::Lt), (max, CmpOp::Gt)] {
+            let bound_temp = TempI::new();
+            code_sequence.push(ThreeAddressCode::StoreI {
+                lhs: LValueI::Temp(bound_temp),
+                rhs: bound.into(),
+            });
+            code_sequence.push(compare_and_branch(
+                cmp_op,
+                selector.clone().into(),
+                LValueI::Temp(bound_temp).into(),
+                default_label,
+            ));
+        }
+
+        case_binary_search(&selector, &arms, default_label, &mut code_sequence);
+        code_sequence
+    }
+
+    /// Generates the 3AC that searches the `arms`, sorted by
+    /// value, for the one whose value equals `selector`.
+    fn case_binary_search(
+        selector: &LValueI,
+        arms: &[(i32, Label)],
+        default_label: Label,
+        code_sequence: &mut Vec<ThreeAddressCode>,
+    ) {
+        if arms.len() <= 2 {
+            case_compare_chain(selector, arms, default_label, code_sequence);
+            return;
+        }
+
+        let mid = arms.len() / 2;
+        let (value, label) = arms[mid];
+        let upper_half_label = Label::new();
+
+        let value_temp = TempI::new();
+        code_sequence.push(ThreeAddressCode::StoreI {
+            lhs: LValueI::Temp(value_temp),
+            rhs: value.into(),
+        });
+        code_sequence.push(compare_and_branch(
+            CmpOp::Eq,
+            selector.clone().into(),
+            LValueI::Temp(value_temp).into(),
+            label,
+        ));
+        code_sequence.push(compare_and_branch(
+            CmpOp::Gt,
+            selector.clone().into(),
+            LValueI::Temp(value_temp).into(),
+            upper_half_label,
+        ));
+
+        case_binary_search(selector, &arms[..mid], default_label, code_sequence);
+        code_sequence.push(ThreeAddressCode::Label(upper_half_label));
+        case_binary_search(selector, &arms[mid + 1..], default_label, code_sequence);
+    }
+
+    /// Generates the 3AC that compares `selector` against the
+    /// value of each of the `arms` in turn.
+    fn case_compare_chain(
+        selector: &LValueI,
+        arms: &[(i32, Label)],
+        default_label: Label,
+        code_sequence: &mut Vec<ThreeAddressCode>,
+    ) {
+        for &(value, label) in arms {
+            let value_temp = TempI::new();
+            code_sequence.push(ThreeAddressCode::StoreI {
+                lhs: LValueI::Temp(value_temp),
+                rhs: value.into(),
+            });
+            code_sequence.push(compare_and_branch(
+                CmpOp::Eq,
+                selector.clone().into(),
+                LValueI::Temp(value_temp).into(),
+                label,
+            ));
+        }
+
+        code_sequence.push(Jump(default_label));
+    }
+
+    /// Generates the compare-branch 3AC that jumps to
+    /// `label` when `lhs cmp_op rhs` evaluates to true.
+    fn compare_and_branch(
+        cmp_op: CmpOp,
+        lhs: LValue,
+        rhs: LValue,
+        label: Label,
+    ) -> ThreeAddressCode {
+        match (cmp_op, lhs, rhs) {
+            (CmpOp::Lt, LValue::LValueI(lhs), LValue::LValueI(rhs)) => LtI { lhs, rhs, label },
+            (CmpOp::Lt, LValue::LValueF(lhs), LValue::LValueF(rhs)) => LtF { lhs, rhs, label },
+            (CmpOp::Gt, LValue::LValueI(lhs), LValue::LValueI(rhs)) => GtI { lhs, rhs, label },
+            (CmpOp::Gt, LValue::LValueF(lhs), LValue::LValueF(rhs)) => GtF { lhs, rhs, label },
+            (CmpOp::Eq, LValue::LValueI(lhs), LValue::LValueI(rhs)) => EqI { lhs, rhs, label },
+            (CmpOp::Eq, LValue::LValueF(lhs), LValue::LValueF(rhs)) => EqF { lhs, rhs, label },
+            (CmpOp::Ne, LValue::LValueI(lhs), LValue::LValueI(rhs)) => NeI { lhs, rhs, label },
+            (CmpOp::Ne, LValue::LValueF(lhs), LValue::LValueF(rhs)) => NeF { lhs, rhs, label },
+            (CmpOp::Lte, LValue::LValueI(lhs), LValue::LValueI(rhs)) => LteI { lhs, rhs, label },
+            (CmpOp::Lte, LValue::LValueF(lhs), LValue::LValueF(rhs)) => LteF { lhs, rhs, label },
+            (CmpOp::Gte, LValue::LValueI(lhs), LValue::LValueI(rhs)) => GteI { lhs, rhs, label },
+            (CmpOp::Gte, LValue::LValueF(lhs), LValue::LValueF(rhs)) => GteF { lhs, rhs, label },
+            (_, left, right) => panic!(
+                "Invalid AST: Unsupported comparison operand combination. Left: [{:?}], Right: [{:?}]",
+                left.result_type(),
+                right.result_type()
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::ast::ast_node::{
+        AddOp, AstNode, CmpOp, Condition, ConditionKind, Expr, ExprKind, Identifier, MulOp,
+    };
+    use crate::three_addr_code_ir::three_address_code::visit::ThreeAddressCodeVisitor;
+    use crate::three_addr_code_ir::{reset_label_counter, LValue, ResultType};
 
     use super::*;
     use crate::ast::ast_node;
-    use crate::ast::ast_node::AstNode::Stmt;
+    use crate::ast::ast_node::visit::Visitor;
+    use crate::ast::type_check;
+    use crate::diagnostic::{Code, Severity};
+    use crate::error::CompileError;
+    use crate::span::{LineCol, LineMap, Span};
+    use crate::symbol_table::error::SymbolError;
     use crate::symbol_table::symbol::data;
-    use crate::symbol_table::symbol::NumType;
+    use crate::symbol_table::SymbolTable;
+    use serial_test::serial;
     use std::rc::Rc;
 
+    lalrpop_mod!(pub microc);
+
     #[test]
     fn convert_simple_int_expression_ast_to_code_object() {
         // Expression => b*b + a
@@ -893,33 +1909,51 @@ mod test {
         //    (*) (a)
         //   /  \
         // (b)  (b)
-        let ast = AstNode::Expr(Expr::Add {
-            op: AddOp::Add,
-            lhs: Box::new(Expr::Mul {
-                op: MulOp::Mul,
-                lhs: Box::new(Expr::Id(Identifier {
-                    symbol: data::Symbol::NonFunctionScopedSymbol(Rc::new(
-                        data::NonFunctionScopedSymbol::Int {
-                            name: "b".to_string(),
-                        },
-                    )),
-                })),
-                rhs: Box::new(Expr::Id(Identifier {
-                    symbol: data::Symbol::NonFunctionScopedSymbol(Rc::new(
-                        data::NonFunctionScopedSymbol::Int {
-                            name: "b".to_string(),
-                        },
-                    )),
-                })),
-            }),
-            rhs: Box::new(Expr::Id(Identifier {
-                symbol: data::Symbol::NonFunctionScopedSymbol(Rc::new(
-                    data::NonFunctionScopedSymbol::Int {
-                        name: "a".to_string(),
+        let ast = AstNode::Expr(Expr::new(
+            ExprKind::Add {
+                op: AddOp::Add,
+                lhs: Box::new(Expr::new(
+                    ExprKind::Mul {
+                        op: MulOp::Mul,
+                        lhs: Box::new(Expr::new(
+                            ExprKind::Id(Identifier {
+                                symbol: data::Symbol::NonFunctionScopedSymbol(Rc::new(
+                                    data::NonFunctionScopedSymbol::Int {
+                                        name: "b".to_string(),
+                                    },
+                                )),
+                                span: Span::default(),
+                            }),
+                            Span::default(),
+                        )),
+                        rhs: Box::new(Expr::new(
+                            ExprKind::Id(Identifier {
+                                symbol: data::Symbol::NonFunctionScopedSymbol(Rc::new(
+                                    data::NonFunctionScopedSymbol::Int {
+                                        name: "b".to_string(),
+                                    },
+                                )),
+                                span: Span::default(),
+                            }),
+                            Span::default(),
+                        )),
                     },
+                    Span::default(),
                 )),
-            })),
-        });
+                rhs: Box::new(Expr::new(
+                    ExprKind::Id(Identifier {
+                        symbol: data::Symbol::NonFunctionScopedSymbol(Rc::new(
+                            data::NonFunctionScopedSymbol::Int {
+                                name: "a".to_string(),
+                            },
+                        )),
+                        span: Span::default(),
+                    }),
+                    Span::default(),
+                )),
+            },
+            Span::default(),
+        ));
 
         let mut visitor = ThreeAddressCodeVisitor;
 
@@ -944,33 +1978,51 @@ mod test {
         //    (*) (a)
         //   /  \
         // (b)  (b)
-        let ast = AstNode::Expr(Expr::Add {
-            op: AddOp::Add,
-            lhs: Box::new(Expr::Mul {
-                op: MulOp::Mul,
-                lhs: Box::new(Expr::Id(Identifier {
-                    symbol: data::Symbol::NonFunctionScopedSymbol(Rc::new(
-                        data::NonFunctionScopedSymbol::Float {
-                            name: "b".to_string(),
-                        },
-                    )),
-                })),
-                rhs: Box::new(Expr::Id(Identifier {
-                    symbol: data::Symbol::NonFunctionScopedSymbol(Rc::new(
-                        data::NonFunctionScopedSymbol::Float {
-                            name: "b".to_string(),
-                        },
-                    )),
-                })),
-            }),
-            rhs: Box::new(Expr::Id(Identifier {
-                symbol: data::Symbol::NonFunctionScopedSymbol(Rc::new(
-                    data::NonFunctionScopedSymbol::Float {
-                        name: "a".to_string(),
+        let ast = AstNode::Expr(Expr::new(
+            ExprKind::Add {
+                op: AddOp::Add,
+                lhs: Box::new(Expr::new(
+                    ExprKind::Mul {
+                        op: MulOp::Mul,
+                        lhs: Box::new(Expr::new(
+                            ExprKind::Id(Identifier {
+                                symbol: data::Symbol::NonFunctionScopedSymbol(Rc::new(
+                                    data::NonFunctionScopedSymbol::Float {
+                                        name: "b".to_string(),
+                                    },
+                                )),
+                                span: Span::default(),
+                            }),
+                            Span::default(),
+                        )),
+                        rhs: Box::new(Expr::new(
+                            ExprKind::Id(Identifier {
+                                symbol: data::Symbol::NonFunctionScopedSymbol(Rc::new(
+                                    data::NonFunctionScopedSymbol::Float {
+                                        name: "b".to_string(),
+                                    },
+                                )),
+                                span: Span::default(),
+                            }),
+                            Span::default(),
+                        )),
                     },
+                    Span::default(),
                 )),
-            })),
-        });
+                rhs: Box::new(Expr::new(
+                    ExprKind::Id(Identifier {
+                        symbol: data::Symbol::NonFunctionScopedSymbol(Rc::new(
+                            data::NonFunctionScopedSymbol::Float {
+                                name: "a".to_string(),
+                            },
+                        )),
+                        span: Span::default(),
+                    }),
+                    Span::default(),
+                )),
+            },
+            Span::default(),
+        ));
 
         let mut visitor = ThreeAddressCodeVisitor;
 
@@ -983,37 +2035,931 @@ mod test {
         assert_eq!(2, code_object.code_sequence.len());
     }
 
+    #[test]
+    fn convert_negative_literal_folds_to_single_store() {
+        let ast = AstNode::Expr(Expr::new(
+            ExprKind::Neg(Box::new(Expr::new(
+                ExprKind::IntLiteral(1),
+                Span::default(),
+            ))),
+            Span::default(),
+        ));
+
+        let mut visitor = ThreeAddressCodeVisitor;
+
+        let code_object = visitor.walk_ast(ast);
+
+        assert!(matches!(
+            code_object.code_sequence.as_slice(),
+            [ThreeAddressCode::StoreI {
+                rhs: RValueI::RValue(-1),
+                ..
+            }]
+        ));
+        assert_eq!(ResultType::Int, code_object.result_type().unwrap());
+    }
+
+    #[test]
+    #[serial]
+    fn convert_smallest_int_literal_to_single_store() {
+        let program = r"
+            PROGRAM test
+            BEGIN
+                INT a;
+
+                FUNCTION VOID main()
+                BEGIN
+                    a := -2147483648;
+                END
+            END
+        ";
+
+        let mut result = microc::ProgramParser::new().parse(program).unwrap();
+        let main = result.remove(0);
+
+        let mut visitor = ThreeAddressCodeVisitor;
+        let code_sequence: Vec<String> = visitor
+            .walk_ast(main)
+            .code_sequence
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+
+        assert_eq!(
+            vec!["STOREI -2147483648 $T1", "STOREI $T1 a"],
+            code_sequence[2..4]
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn convert_call_statements_to_calling_sequence() {
+        let program = r"
+            PROGRAM test
+            BEGIN
+                INT a;
+
+                FUNCTION VOID log(INT x)
+                BEGIN
+                    WRITE(x);
+                END
+
+                FUNCTION INT next(INT x)
+                BEGIN
+                    RETURN x + 1;
+                END
+
+                FUNCTION VOID main()
+                BEGIN
+                    log(a);
+                    next(a);
+                END
+            END
+        ";
+
+        let mut result = microc::ProgramParser::new().parse(program).unwrap();
+        let main = result.remove(0);
+
+        let mut visitor = ThreeAddressCodeVisitor;
+        let calls = visitor
+            .walk_ast(main)
+            .code_sequence
+            .into_iter()
+            .filter(|tac| {
+                matches!(
+                    tac,
+                    ThreeAddressCode::PushEmpty
+                        | ThreeAddressCode::PushI(_)
+                        | ThreeAddressCode::Jsr(_)
+                        | ThreeAddressCode::PopEmpty
+                        | ThreeAddressCode::PopI(_)
+                )
+            })
+            .collect::<Vec<_>>();
+
+        // No return slot is reserved for the VOID function and the
+        // result of the INT function is popped off and discarded.
+        assert!(matches!(
+            calls.as_slice(),
+            [
+                ThreeAddressCode::PushI(_),
+                ThreeAddressCode::Jsr(_),
+                ThreeAddressCode::PopEmpty,
+                ThreeAddressCode::PushEmpty,
+                ThreeAddressCode::PushI(_),
+                ThreeAddressCode::Jsr(_),
+                ThreeAddressCode::PopEmpty,
+                ThreeAddressCode::PopEmpty,
+            ]
+        ));
+    }
+
+    #[test]
+    #[serial]
+    fn assigning_void_function_call_result_is_rejected() {
+        let program = r"
+            PROGRAM test
+            BEGIN
+                INT a;
+
+                FUNCTION VOID log(INT x)
+                BEGIN
+                    WRITE(x);
+                END
+
+                FUNCTION VOID main()
+                BEGIN
+                    a := log(a);
+                END
+            END
+        ";
+
+        assert!(microc::ProgramParser::new().parse(program).is_ok());
+        assert!(matches!(
+            SymbolTable::symbol_errors().as_slice(),
+            [CompileError::Symbol(SymbolError::UseVoidFunctionResult(_), diagnostic)]
+                if diagnostic.code == Code::VoidValue
+        ));
+    }
+
+    #[test]
+    #[serial]
+    fn convert_early_bare_return_and_append_implicit_epilogue() {
+        let program = r"
+            PROGRAM test
+            BEGIN
+                INT a;
+
+                FUNCTION VOID main()
+                BEGIN
+                    IF (a > 0)
+                        RETURN;
+                    FI
+                    WRITE(a);
+                END
+            END
+        ";
+
+        let mut result = microc::ProgramParser::new().parse(program).unwrap();
+        let main = result.remove(0);
+
+        let mut visitor = ThreeAddressCodeVisitor;
+        let code_sequence = visitor.walk_ast(main).code_sequence;
+        let returns = code_sequence.iter().filter(|tac| tac.is_return()).count();
+
+        // One return for the early exit and one for the implicit epilogue.
+        assert_eq!(2, returns);
+        assert!(code_sequence.last().unwrap().is_return());
+    }
+
+    #[test]
+    #[serial]
+    fn bare_return_in_function_returning_a_value_is_rejected() {
+        let program = r"
+            PROGRAM test
+            BEGIN
+                FUNCTION INT main()
+                BEGIN
+                    RETURN;
+                END
+            END
+        ";
+
+        assert!(microc::ProgramParser::new().parse(program).is_ok());
+        assert!(matches!(
+            SymbolTable::symbol_errors().as_slice(),
+            [CompileError::Semantic(diagnostic)]
+                if diagnostic.code == Code::InvalidControlFlow
+        ));
+    }
+
+    #[test]
+    #[serial]
+    fn break_outside_of_loop_is_rejected() {
+        let program = r"
+            PROGRAM test
+            BEGIN
+                FUNCTION VOID main()
+                BEGIN
+                    DO
+                    WHILE (1 = 1);
+                    BREAK;
+                END
+            END
+        ";
+
+        assert!(microc::ProgramParser::new().parse(program).is_ok());
+        assert!(matches!(
+            SymbolTable::symbol_errors().as_slice(),
+            [CompileError::Semantic(diagnostic)]
+                if diagnostic.code == Code::InvalidControlFlow
+        ));
+    }
+
+    #[test]
+    #[serial]
+    fn convert_array_element_reads_and_writes() {
+        let program = r"
+            PROGRAM test
+            BEGIN
+                INT a[4];
+
+                FUNCTION VOID main()
+                BEGIN
+                    INT i;
+                    FLOAT b[2];
+                    a[i] := a[i + 1];
+                    b[1] := b[0];
+                END
+            END
+        ";
+
+        let mut result = microc::ProgramParser::new().parse(program).unwrap();
+        let main = result.remove(0);
+
+        // Every element of a local array takes up a stack slot.
+        assert_eq!(
+            3,
+            SymbolTable::function_symbol_for_name("main")
+                .unwrap()
+                .num_locals()
+        );
+
+        let mut visitor = ThreeAddressCodeVisitor;
+        let code_sequence: Vec<String> = visitor
+            .walk_ast(main)
+            .code_sequence
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+
+        assert_eq!(
+            vec![
+                "LABEL main",
+                "LINK",
+                "STOREI 1 $T1",
+                "ADDI $L1 $T1 $T2",
+                "LOADI a[$T2] $T3",
+                "STOREI $T3 a[$L1]",
+                "STOREI 1 $T4",
+                "STOREI 0 $T5",
+                "LOADF $L2[$T5] $T6",
+                "STOREF $T6 $L2[$T4]",
+                "RET",
+            ],
+            code_sequence
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn convert_mixed_int_and_float_operands_and_casts() {
+        let program = r"
+            PROGRAM test
+            BEGIN
+                INT i;
+                FLOAT f;
+
+                FUNCTION VOID main()
+                BEGIN
+                    f := i * 2.0;
+                    f := 1;
+                    i := INT(f);
+                    IF (i < f)
+                    FI
+                END
+            END
+        ";
+
+        let mut result = microc::ProgramParser::new().parse(program).unwrap();
+        let main = result.remove(0);
+
+        let mut visitor = ThreeAddressCodeVisitor;
+        let code_sequence: Vec<String> = visitor
+            .walk_ast(main)
+            .code_sequence
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+
+        assert_eq!(
+            vec![
+                "ITOF i $T2",
+                "STOREF 2 $T1",
+                "MULTF $T2 $T1 $T3",
+                "STOREF $T3 f",
+                // INT literals are converted at compile time
+                "STOREF 1 $T5",
+                "STOREF $T5 f",
+                "FTOI f $T6",
+                "STOREI $T6 i",
+                "ITOF i $T7",
+            ],
+            code_sequence[2..11]
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn convert_write_of_expressions_and_string_literals() {
+        let program = r#"
+            PROGRAM test
+            BEGIN
+                INT a;
+                STRING newline := "\n";
+
+                FUNCTION VOID main()
+                BEGIN
+                    WRITE(a, a + 1, "sum\n", newline, 2.5, "sum\n");
+                END
+            END
+        "#;
+
+        let mut result = microc::ProgramParser::new().parse(program).unwrap();
+        let main = result.remove(0);
+
+        let mut visitor = ThreeAddressCodeVisitor;
+        let code_sequence: Vec<String> = visitor
+            .walk_ast(main)
+            .code_sequence
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+
+        assert_eq!(
+            vec![
+                "WRITEI a",
+                "STOREI 1 $T1",
+                "ADDI a $T1 $T2",
+                "WRITEI $T2",
+                "WRITES string_literal_0",
+                "WRITES newline",
+                "STOREF 2.5 $T3",
+                "WRITEF $T3",
+                // Identical literals share a declaration
+                "WRITES string_literal_0",
+            ],
+            code_sequence[2..11]
+        );
+        assert_eq!(1, SymbolTable::string_literals().len());
+    }
+
+    #[test]
+    #[serial]
+    fn array_used_without_an_index_is_rejected() {
+        let program = r"
+            PROGRAM test
+            BEGIN
+                INT a[4], b;
+
+                FUNCTION VOID main()
+                BEGIN
+                    b := a + 1;
+                END
+            END
+        ";
+
+        assert!(microc::ProgramParser::new().parse(program).is_ok());
+        assert!(matches!(
+            SymbolTable::symbol_errors().as_slice(),
+            [CompileError::Semantic(diagnostic)]
+                if diagnostic.code == Code::InvalidArray
+        ));
+    }
+
+    #[test]
+    #[serial]
+    fn indexing_a_non_array_is_rejected() {
+        let program = r"
+            PROGRAM test
+            BEGIN
+                INT a, b;
+
+                FUNCTION VOID main()
+                BEGIN
+                    b := a[1];
+                END
+            END
+        ";
+
+        assert!(microc::ProgramParser::new().parse(program).is_ok());
+        assert!(matches!(
+            SymbolTable::symbol_errors().as_slice(),
+            [CompileError::Semantic(diagnostic)]
+                if diagnostic.code == Code::InvalidArray
+        ));
+    }
+
+    #[test]
+    #[serial]
+    fn array_without_elements_is_rejected() {
+        let program = r"
+            PROGRAM test
+            BEGIN
+                INT a[0];
+
+                FUNCTION VOID main()
+                BEGIN
+                END
+            END
+        ";
+
+        assert!(microc::ProgramParser::new().parse(program).is_ok());
+        assert!(matches!(
+            SymbolTable::symbol_errors().as_slice(),
+            [CompileError::Semantic(diagnostic)]
+                if diagnostic.code == Code::InvalidArray
+        ));
+    }
+
+    #[test]
+    #[serial]
+    fn convert_variable_initializers() {
+        let program = r"
+            PROGRAM test
+            BEGIN
+                INT n := 2 * (3 + 2);
+                FLOAT f := 1;
+
+                FUNCTION VOID main()
+                BEGIN
+                    INT i := n, j;
+                    FLOAT g := i;
+
+                    WRITE(j);
+                END
+            END
+        ";
+
+        let mut result = microc::ProgramParser::new().parse(program).unwrap();
+        let main = result.remove(0);
+
+        let initializers: Vec<(String, data::NumValue)> = SymbolTable::global_initializers()
+            .into_iter()
+            .map(|(symbol, value)| (symbol.to_string(), value))
+            .collect();
+        assert_eq!(
+            vec![
+                ("n".to_owned(), data::NumValue::Int(10)),
+                // Initial values are converted to the type of the variable
+                ("f".to_owned(), data::NumValue::Float(1.0)),
+            ],
+            initializers
+        );
+
+        let mut visitor = ThreeAddressCodeVisitor;
+        let code_sequence: Vec<String> = visitor
+            .walk_ast(main)
+            .code_sequence
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+
+        // Locals are initialized on entry to the function
+        assert_eq!(
+            vec![
+                "STOREI n $L1",
+                "ITOF $L1 $T1",
+                "STOREF $T1 $L3",
+                "WRITEI $L2"
+            ],
+            code_sequence[2..6]
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn non_constant_global_initializer_is_rejected() {
+        let program = r"
+            PROGRAM test
+            BEGIN
+                INT a;
+                INT b := a + 1;
+
+                FUNCTION VOID main()
+                BEGIN
+                END
+            END
+        ";
+
+        assert!(microc::ProgramParser::new().parse(program).is_ok());
+        assert!(matches!(
+            SymbolTable::symbol_errors().as_slice(),
+            [CompileError::Semantic(diagnostic)]
+                if diagnostic.code == Code::NotConstant
+        ));
+    }
+
+    #[test]
+    #[serial]
+    fn block_locals_take_up_stack_slots_reused_by_sibling_blocks() {
+        let program = r"
+            PROGRAM test
+            BEGIN
+                FUNCTION VOID main()
+                BEGIN
+                    INT a;
+
+                    IF (a > 0)
+                        INT b := 1;
+                        FLOAT c := 2.5;
+                    ELSE
+                        INT d := 3;
+                    FI
+                    FOR (; a > 0;)
+                        INT e[3];
+                        e[2] := 4;
+                    ROF
+                END
+            END
+        ";
+
+        let mut result = microc::ProgramParser::new().parse(program).unwrap();
+        let main = result.remove(0);
+
+        // `a`, followed by `b` and `c`, whose
+        // slots are reused by `d` and then `e`
+        assert_eq!(
+            4,
+            SymbolTable::function_symbol_for_name("main")
+                .unwrap()
+                .num_locals()
+        );
+
+        let mut visitor = ThreeAddressCodeVisitor;
+        let code_sequence: Vec<String> = visitor
+            .walk_ast(main)
+            .code_sequence
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+
+        assert!(code_sequence.contains(&"STOREI $T2 $L2".to_owned()));
+        assert!(code_sequence.contains(&"STOREF $T3 $L3".to_owned()));
+        assert!(code_sequence.contains(&"STOREI $T4 $L2".to_owned()));
+    }
+
+    #[test]
+    #[serial]
+    fn call_to_function_declared_by_prototype_resolves_to_its_definition() {
+        let program = r"
+            PROGRAM test
+            BEGIN
+                FUNCTION INT isEven(INT n);
+
+                FUNCTION INT isOdd(INT n)
+                BEGIN
+                    RETURN isEven(n - 1);
+                END
+
+                FUNCTION INT isEven(INT n)
+                BEGIN
+                    INT unused;
+                    RETURN isOdd(n - 1);
+                END
+            END
+        ";
+
+        assert!(microc::ProgramParser::new().parse(program).is_ok());
+        assert_eq!(
+            1,
+            SymbolTable::function_symbol_for_name("isEven")
+                .unwrap()
+                .num_locals()
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn call_to_function_that_is_never_defined_is_rejected() {
+        let program = r"
+            PROGRAM test
+            BEGIN
+                FUNCTION VOID undefined(INT n);
+
+                FUNCTION VOID main()
+                BEGIN
+                    undefined(1);
+                END
+            END
+        ";
+
+        assert!(microc::ProgramParser::new().parse(program).is_ok());
+        assert!(matches!(
+            SymbolTable::symbol_errors().as_slice(),
+            [CompileError::Symbol(SymbolError::UseUndefinedFunction(_), diagnostic)]
+                if diagnostic.code == Code::UndefinedFunction
+        ));
+    }
+
+    #[test]
+    #[serial]
+    fn definition_not_matching_its_prototype_is_rejected() {
+        let program = r"
+            PROGRAM test
+            BEGIN
+                FUNCTION INT f(INT n);
+
+                FUNCTION INT f(FLOAT n)
+                BEGIN
+                    RETURN 1;
+                END
+            END
+        ";
+
+        assert!(microc::ProgramParser::new().parse(program).is_ok());
+        assert!(matches!(
+            SymbolTable::symbol_errors().as_slice(),
+            [CompileError::Symbol(SymbolError::MismatchedFunctionPrototype(_), diagnostic)]
+                if diagnostic.code == Code::PrototypeMismatch
+        ));
+    }
+
+    #[test]
+    #[serial]
+    fn convert_constants_to_literals() {
+        let program = r"
+            PROGRAM test
+            BEGIN
+                CONST INT N := 4;
+                CONST FLOAT HALF := N / 8.0;
+                INT a[N + 1];
+
+                FUNCTION VOID main()
+                BEGIN
+                    a[N] := N;
+                    WRITE(HALF);
+                END
+            END
+        ";
+
+        let mut result = microc::ProgramParser::new().parse(program).unwrap();
+        let main = result.remove(0);
+
+        let mut visitor = ThreeAddressCodeVisitor;
+        let code_sequence: Vec<String> = visitor
+            .walk_ast(main)
+            .code_sequence
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+
+        // Constants are replaced by their values and take up no storage
+        assert_eq!(
+            vec![
+                "STOREI 4 $T1",
+                "STOREI 4 $T2",
+                "STOREI $T2 a[$T1]",
+                "STOREF 0.5 $T3",
+                "WRITEF $T3"
+            ],
+            code_sequence[2..7]
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn convert_local_constants_to_literals() {
+        let program = r"
+            PROGRAM test
+            BEGIN
+                FUNCTION VOID main()
+                BEGIN
+                    CONST INT N := 4;
+                    INT a;
+
+                    IF (TRUE)
+                        CONST INT M := N * 2;
+                        a := M;
+                    FI
+                END
+            END
+        ";
+
+        let mut result = microc::ProgramParser::new().parse(program).unwrap();
+        let main = result.remove(0);
+
+        let mut visitor = ThreeAddressCodeVisitor;
+        let code_sequence: Vec<String> = visitor
+            .walk_ast(main)
+            .code_sequence
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+
+        // Local constants take up no stack slot either,
+        // so `a` is the first local of the function
+        assert_eq!(vec!["STOREI 8 $T1", "STOREI $T1 $L1"], code_sequence[2..4]);
+    }
+
+    #[test]
+    #[serial]
+    fn assignment_to_constant_is_rejected() {
+        let program = r"
+            PROGRAM test
+            BEGIN
+                CONST INT N := 4;
+
+                FUNCTION VOID main()
+                BEGIN
+                    N := 5;
+                END
+            END
+        ";
+
+        assert!(microc::ProgramParser::new().parse(program).is_ok());
+        assert!(matches!(
+            SymbolTable::symbol_errors().as_slice(),
+            [CompileError::Semantic(diagnostic)]
+                if diagnostic.code == Code::InvalidAssignment
+        ));
+    }
+
+    #[test]
+    #[serial]
+    fn read_into_constant_is_rejected() {
+        let program = r"
+            PROGRAM test
+            BEGIN
+                CONST FLOAT PI := 3.14;
+
+                FUNCTION VOID main()
+                BEGIN
+                    READ(PI);
+                END
+            END
+        ";
+
+        assert!(microc::ProgramParser::new().parse(program).is_ok());
+        assert!(matches!(
+            SymbolTable::symbol_errors().as_slice(),
+            [CompileError::Semantic(diagnostic)]
+                if diagnostic.code == Code::InvalidAssignment
+        ));
+    }
+
+    #[test]
+    #[serial]
+    fn non_constant_const_value_is_rejected() {
+        let program = r"
+            PROGRAM test
+            BEGIN
+                INT a;
+                CONST INT N := a;
+
+                FUNCTION VOID main()
+                BEGIN
+                END
+            END
+        ";
+
+        assert!(microc::ProgramParser::new().parse(program).is_ok());
+        assert!(matches!(
+            SymbolTable::symbol_errors().as_slice(),
+            [CompileError::Semantic(diagnostic)]
+                if diagnostic.code == Code::NotConstant
+        ));
+    }
+
+    #[test]
+    #[serial]
+    fn convert_modulo_expression() {
+        let program = r"
+            PROGRAM test
+            BEGIN
+                FUNCTION INT f(INT a, INT b)
+                BEGIN
+                    RETURN a % b + 7 % 4;
+                END
+            END
+        ";
+
+        let mut result = microc::ProgramParser::new().parse(program).unwrap();
+        let f = result.remove(0);
+
+        let mut visitor = ThreeAddressCodeVisitor;
+        let code_sequence: Vec<String> = visitor
+            .walk_ast(f)
+            .code_sequence
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+
+        assert_eq!(
+            vec![
+                "MODI $P2 $P3 $T1",
+                "STOREI 7 $T2",
+                "STOREI 4 $T3",
+                "MODI $T2 $T3 $T4",
+                "ADDI $T1 $T4 $T5"
+            ],
+            code_sequence[2..7]
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn convert_compound_assignments_into_single_op_on_target() {
+        reset_label_counter();
+
+        let program = r"
+            PROGRAM test
+            BEGIN
+                FLOAT f;
+                INT a[4];
+
+                FUNCTION VOID main()
+                BEGIN
+                    INT i;
+
+                    FOR (i := 0; i < 4; i += 1)
+                        a[i] *= 2;
+                    ROF
+                    f -= i;
+                END
+            END
+        ";
+
+        let mut result = microc::ProgramParser::new().parse(program).unwrap();
+        let main = result.remove(0);
+
+        let mut visitor = ThreeAddressCodeVisitor;
+        let code_sequence: Vec<String> = visitor
+            .walk_ast(main)
+            .code_sequence
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+
+        // Variable targets take the result of the op directly, while
+        // array elements are loaded, updated and stored back.
+        assert_eq!(
+            vec![
+                "LOADI a[$L1] $T4",
+                "STOREI 2 $T5",
+                "MULTI $T4 $T5 $T6",
+                "STOREI $T6 a[$L1]",
+                "LABEL label3",
+                "STOREI 1 $T3",
+                "ADDI $L1 $T3 $L1",
+                "JUMP label1",
+                "LABEL label2",
+                "ITOF $L1 $T7",
+                "SUBF f $T7 f"
+            ],
+            code_sequence[7..18]
+        );
+    }
+
     #[test]
     #[should_panic]
     fn convert_math_expression_with_string_identifier_panics() {
-        let ast = AstNode::Expr(Expr::Add {
-            op: AddOp::Add,
-            lhs: Box::new(Expr::Mul {
-                op: MulOp::Mul,
-                lhs: Box::new(Expr::Id(Identifier {
-                    symbol: data::Symbol::NonFunctionScopedSymbol(Rc::new(
-                        data::NonFunctionScopedSymbol::String {
-                            name: "b".to_string(),
-                            value: "value".to_string(),
-                        },
-                    )),
-                })),
-                rhs: Box::new(Expr::Id(Identifier {
-                    symbol: data::Symbol::NonFunctionScopedSymbol(Rc::new(
-                        data::NonFunctionScopedSymbol::Float {
-                            name: "b".to_string(),
-                        },
-                    )),
-                })),
-            }),
-            rhs: Box::new(Expr::Id(Identifier {
-                symbol: data::Symbol::NonFunctionScopedSymbol(Rc::new(
-                    data::NonFunctionScopedSymbol::Float {
-                        name: "a".to_string(),
+        let ast = AstNode::Expr(Expr::new(
+            ExprKind::Add {
+                op: AddOp::Add,
+                lhs: Box::new(Expr::new(
+                    ExprKind::Mul {
+                        op: MulOp::Mul,
+                        lhs: Box::new(Expr::new(
+                            ExprKind::Id(Identifier {
+                                symbol: data::Symbol::NonFunctionScopedSymbol(Rc::new(
+                                    data::NonFunctionScopedSymbol::String {
+                                        name: "b".to_string(),
+                                        value: "value".to_string(),
+                                    },
+                                )),
+                                span: Span::default(),
+                            }),
+                            Span::default(),
+                        )),
+                        rhs: Box::new(Expr::new(
+                            ExprKind::Id(Identifier {
+                                symbol: data::Symbol::NonFunctionScopedSymbol(Rc::new(
+                                    data::NonFunctionScopedSymbol::Float {
+                                        name: "b".to_string(),
+                                    },
+                                )),
+                                span: Span::default(),
+                            }),
+                            Span::default(),
+                        )),
                     },
+                    Span::default(),
                 )),
-            })),
-        });
+                rhs: Box::new(Expr::new(
+                    ExprKind::Id(Identifier {
+                        symbol: data::Symbol::NonFunctionScopedSymbol(Rc::new(
+                            data::NonFunctionScopedSymbol::Float {
+                                name: "a".to_string(),
+                            },
+                        )),
+                        span: Span::default(),
+                    }),
+                    Span::default(),
+                )),
+            },
+            Span::default(),
+        ));
 
         let mut visitor = ThreeAddressCodeVisitor;
 
@@ -1023,29 +2969,43 @@ mod test {
     #[test]
     #[should_panic]
     fn convert_condition_comparing_string_identifier_panics() {
-        let ast = AstNode::Stmt(ast_node::Stmt::If {
-            condition: Condition {
-                cmp_op: CmpOp::Lt,
-                lhs: Expr::Id(Identifier {
-                    symbol: data::Symbol::NonFunctionScopedSymbol(Rc::new(
-                        data::NonFunctionScopedSymbol::String {
-                            name: "b".to_string(),
-                            value: "value".to_string(),
-                        },
-                    )),
-                }),
-                rhs: Expr::Id(Identifier {
-                    symbol: data::Symbol::NonFunctionScopedSymbol(Rc::new(
-                        data::NonFunctionScopedSymbol::String {
-                            name: "b".to_string(),
-                            value: "value".to_string(),
-                        },
-                    )),
-                }),
+        let ast = AstNode::Stmt(Box::new(ast_node::Stmt::new(
+            ast_node::StmtKind::If {
+                condition: Condition::new(
+                    ConditionKind::Cmp {
+                        cmp_op: CmpOp::Lt,
+                        lhs: Expr::new(
+                            ExprKind::Id(Identifier {
+                                symbol: data::Symbol::NonFunctionScopedSymbol(Rc::new(
+                                    data::NonFunctionScopedSymbol::String {
+                                        name: "b".to_string(),
+                                        value: "value".to_string(),
+                                    },
+                                )),
+                                span: Span::default(),
+                            }),
+                            Span::default(),
+                        ),
+                        rhs: Expr::new(
+                            ExprKind::Id(Identifier {
+                                symbol: data::Symbol::NonFunctionScopedSymbol(Rc::new(
+                                    data::NonFunctionScopedSymbol::String {
+                                        name: "b".to_string(),
+                                        value: "value".to_string(),
+                                    },
+                                )),
+                                span: Span::default(),
+                            }),
+                            Span::default(),
+                        ),
+                    },
+                    Span::default(),
+                ),
+                then_block: vec![],
+                else_block: vec![],
             },
-            then_block: vec![],
-            else_block: vec![],
-        });
+            Span::default(),
+        )));
 
         let mut visitor = ThreeAddressCodeVisitor;
 
@@ -1053,38 +3013,856 @@ mod test {
     }
 
     #[test]
-    #[should_panic]
-    fn convert_math_expression_with_mixed_num_operand_types_panics() {
-        let ast = AstNode::Expr(Expr::Add {
-            op: AddOp::Add,
-            lhs: Box::new(Expr::Mul {
-                op: MulOp::Mul,
-                lhs: Box::new(Expr::Id(Identifier {
+    fn convert_or_condition_short_circuits_on_true_lhs() {
+        // Condition => a < b OR a > b
+        let int_id = |name: &str| {
+            Expr::new(
+                ExprKind::Id(Identifier {
                     symbol: data::Symbol::NonFunctionScopedSymbol(Rc::new(
                         data::NonFunctionScopedSymbol::Int {
-                            name: "b".to_string(),
-                        },
-                    )),
-                })),
-                rhs: Box::new(Expr::Id(Identifier {
-                    symbol: data::Symbol::NonFunctionScopedSymbol(Rc::new(
-                        data::NonFunctionScopedSymbol::Float {
-                            name: "b".to_string(),
+                            name: name.to_string(),
                         },
                     )),
-                })),
-            }),
-            rhs: Box::new(Expr::Id(Identifier {
-                symbol: data::Symbol::NonFunctionScopedSymbol(Rc::new(
-                    data::NonFunctionScopedSymbol::Float {
-                        name: "a".to_string(),
+                    span: Span::default(),
+                }),
+                Span::default(),
+            )
+        };
+        let condition = Condition::new(
+            ConditionKind::Or(
+                Box::new(Condition::new(
+                    ConditionKind::Cmp {
+                        cmp_op: CmpOp::Lt,
+                        lhs: int_id("a"),
+                        rhs: int_id("b"),
                     },
+                    Span::default(),
                 )),
-            })),
-        });
+                Box::new(Condition::new(
+                    ConditionKind::Cmp {
+                        cmp_op: CmpOp::Gt,
+                        lhs: int_id("a"),
+                        rhs: int_id("b"),
+                    },
+                    Span::default(),
+                )),
+            ),
+            Span::default(),
+        );
 
         let mut visitor = ThreeAddressCodeVisitor;
 
-        visitor.walk_ast(ast);
+        let code_object = visitor.visit_condition(condition);
+        let else_label = code_object.jump_to.unwrap();
+
+        // A true lhs skips the rhs, a false rhs jumps to the else label.
+        match code_object.code_sequence.as_slice() {
+            [ThreeAddressCode::LtI {
+                label: skip_label, ..
+            }, ThreeAddressCode::LteI { label, .. }, ThreeAddressCode::Label(label_after_rhs)] => {
+                assert_eq!(skip_label, label_after_rhs);
+                assert_eq!(else_label, *label);
+            }
+            code_sequence => panic!("Unexpected code sequence: {:?}", code_sequence),
+        }
+    }
+
+    #[test]
+    fn convert_math_expression_with_mixed_num_operand_types_promotes_int_operand() {
+        let ast = AstNode::Expr(Expr::new(
+            ExprKind::Add {
+                op: AddOp::Add,
+                lhs: Box::new(Expr::new(
+                    ExprKind::Mul {
+                        op: MulOp::Mul,
+                        lhs: Box::new(Expr::new(
+                            ExprKind::Id(Identifier {
+                                symbol: data::Symbol::NonFunctionScopedSymbol(Rc::new(
+                                    data::NonFunctionScopedSymbol::Int {
+                                        name: "b".to_string(),
+                                    },
+                                )),
+                                span: Span::default(),
+                            }),
+                            Span::default(),
+                        )),
+                        rhs: Box::new(Expr::new(
+                            ExprKind::Id(Identifier {
+                                symbol: data::Symbol::NonFunctionScopedSymbol(Rc::new(
+                                    data::NonFunctionScopedSymbol::Float {
+                                        name: "b".to_string(),
+                                    },
+                                )),
+                                span: Span::default(),
+                            }),
+                            Span::default(),
+                        )),
+                    },
+                    Span::default(),
+                )),
+                rhs: Box::new(Expr::new(
+                    ExprKind::Id(Identifier {
+                        symbol: data::Symbol::NonFunctionScopedSymbol(Rc::new(
+                            data::NonFunctionScopedSymbol::Float {
+                                name: "a".to_string(),
+                            },
+                        )),
+                        span: Span::default(),
+                    }),
+                    Span::default(),
+                )),
+            },
+            Span::default(),
+        ));
+
+        let mut visitor = ThreeAddressCodeVisitor;
+        let code_object = visitor.walk_ast(ast);
+
+        assert_eq!(ResultType::Float, code_object.result_type().unwrap());
+        assert!(matches!(
+            code_object.code_sequence[0],
+            ThreeAddressCode::IntToFloat { .. }
+        ));
+    }
+
+    #[test]
+    #[serial]
+    fn convert_bool_values_into_int_zero_or_one() {
+        reset_label_counter();
+
+        let program = r"
+            PROGRAM test
+            BEGIN
+                FUNCTION VOID main()
+                BEGIN
+                    INT a, b;
+                    BOOL p;
+
+                    p := a < b;
+                    IF (p)
+                        a := 1;
+                    FI
+                END
+            END
+        ";
+
+        let mut result = microc::ProgramParser::new().parse(program).unwrap();
+        let main = result.remove(0);
+
+        let mut visitor = ThreeAddressCodeVisitor;
+        let code_sequence: Vec<String> = visitor
+            .walk_ast(main)
+            .code_sequence
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+
+        // A comparison used as a value is branched on to store 0 or 1,
+        // while a BOOL used as a condition is compared against 0.
+        assert_eq!(
+            vec![
+                "GE $L1 $L2 label1",
+                "STOREI 1 $T1",
+                "JUMP label2",
+                "LABEL label1",
+                "STOREI 0 $T1",
+                "LABEL label2",
+                "STOREI $T1 $L3",
+                "STOREI 0 $T2",
+                "EQ $L3 $T2 label3",
+            ],
+            code_sequence[2..11]
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn comparison_assigned_to_int_is_rejected() {
+        let program = r"
+            PROGRAM test
+            BEGIN
+                INT a, b, c;
+
+                FUNCTION VOID main()
+                BEGIN
+                    c := a < b;
+                END
+            END
+        ";
+
+        let mut result = microc::ProgramParser::new().parse(program).unwrap();
+
+        assert!(matches!(
+            type_check::check_program(&mut result).as_slice(),
+            [CompileError::Type(_)]
+        ));
+    }
+
+    #[test]
+    #[serial]
+    fn arithmetic_on_bool_is_rejected() {
+        let program = r"
+            PROGRAM test
+            BEGIN
+                INT a;
+                BOOL p;
+
+                FUNCTION VOID main()
+                BEGIN
+                    a := p + 1;
+                END
+            END
+        ";
+
+        let mut result = microc::ProgramParser::new().parse(program).unwrap();
+
+        assert!(matches!(
+            type_check::check_program(&mut result).as_slice(),
+            [CompileError::Type(_)]
+        ));
+    }
+    #[test]
+    #[serial]
+    fn convert_conditional_expression_into_branches_writing_one_temp() {
+        reset_label_counter();
+
+        let program = r"
+            PROGRAM test
+            BEGIN
+                FUNCTION VOID main()
+                BEGIN
+                    INT a;
+                    FLOAT f;
+
+                    f := IF (a > 0) THEN a ELSE 0.5 FI;
+                END
+            END
+        ";
+
+        let mut result = microc::ProgramParser::new().parse(program).unwrap();
+        let main = result.remove(0);
+
+        let mut visitor = ThreeAddressCodeVisitor;
+        let code_sequence: Vec<String> = visitor
+            .walk_ast(main)
+            .code_sequence
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+
+        // The INT value is converted to FLOAT in its own branch.
+        assert_eq!(
+            vec![
+                "STOREI 0 $T1",
+                "LE $L1 $T1 label1",
+                "ITOF $L1 $T3",
+                "STOREF $T3 $T4",
+                "JUMP label2",
+                "LABEL label1",
+                "STOREF 0.5 $T2",
+                "STOREF $T2 $T4",
+                "LABEL label2",
+                "STOREF $T4 $L2",
+            ],
+            code_sequence[2..12]
+        );
+    }
+    #[test]
+    #[serial]
+    fn convert_sparse_case_into_compare_chain() {
+        reset_label_counter();
+
+        let program = r"
+            PROGRAM test
+            BEGIN
+                FUNCTION VOID main()
+                BEGIN
+                    INT a;
+
+                    CASE (a) OF
+                        10: a := 1;
+                        -20: a := 2;
+                        DEFAULT: a := 3;
+                    ESAC
+                END
+            END
+        ";
+
+        let mut result = microc::ProgramParser::new().parse(program).unwrap();
+        let main = result.remove(0);
+
+        let mut visitor = ThreeAddressCodeVisitor;
+        let code_sequence: Vec<String> = visitor
+            .walk_ast(main)
+            .code_sequence
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+
+        // Every arm jumps past the rest, while the
+        // `DEFAULT` block is last and falls through.
+        assert_eq!(
+            vec![
+                "STOREI 10 $T1",
+                "EQ $L1 $T1 label1",
+                "STOREI -20 $T2",
+                "EQ $L1 $T2 label2",
+                "JUMP label3",
+                "LABEL label1",
+                "STOREI 1 $T3",
+                "STOREI $T3 $L1",
+                "JUMP label4",
+                "LABEL label2",
+                "STOREI 2 $T4",
+                "STOREI $T4 $L1",
+                "JUMP label4",
+                "LABEL label3",
+                "STOREI 3 $T5",
+                "STOREI $T5 $L1",
+                "LABEL label4",
+            ],
+            code_sequence[2..19]
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn convert_dense_case_into_range_check_and_binary_search() {
+        reset_label_counter();
+
+        let program = r"
+            PROGRAM test
+            BEGIN
+                FUNCTION VOID main()
+                BEGIN
+                    INT a;
+
+                    CASE (a) OF
+                        3: a := 30;
+                        1: a := 10;
+                        4: a := 40;
+                        2: a := 20;
+                    ESAC
+                END
+            END
+        ";
+
+        let mut result = microc::ProgramParser::new().parse(program).unwrap();
+        let main = result.remove(0);
+
+        let mut visitor = ThreeAddressCodeVisitor;
+        let code_sequence: Vec<String> = visitor
+            .walk_ast(main)
+            .code_sequence
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+
+        // label5 is the (empty) `DEFAULT` block.
+        assert_eq!(
+            vec![
+                "STOREI 1 $T1",
+                "LT $L1 $T1 label5",
+                "STOREI 4 $T2",
+                "GT $L1 $T2 label5",
+                "STOREI 3 $T3",
+                "EQ $L1 $T3 label1",
+                "GT $L1 $T3 label7",
+                "STOREI 1 $T4",
+                "EQ $L1 $T4 label2",
+                "STOREI 2 $T5",
+                "EQ $L1 $T5 label4",
+                "JUMP label5",
+                "LABEL label7",
+                "STOREI 4 $T6",
+                "EQ $L1 $T6 label3",
+                "JUMP label5",
+            ],
+            code_sequence[2..18]
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn duplicate_case_label_is_rejected() {
+        let program = r"
+            PROGRAM test
+            BEGIN
+                INT a;
+
+                FUNCTION VOID main()
+                BEGIN
+                    CASE (a) OF
+                        1: a := 2;
+                        1: a := 3;
+                    ESAC
+                END
+            END
+        ";
+
+        assert!(microc::ProgramParser::new().parse(program).is_ok());
+        assert!(matches!(
+            SymbolTable::symbol_errors().as_slice(),
+            [CompileError::Semantic(diagnostic)]
+                if diagnostic.code == Code::DuplicateCaseLabel
+        ));
+    }
+
+    #[test]
+    #[serial]
+    fn convert_record_fields_into_slots_passed_by_value() {
+        let program = r"
+            PROGRAM test
+            BEGIN
+                RECORD pair
+                BEGIN
+                    INT a;
+                    FLOAT b;
+                END
+
+                RECORD pair g;
+
+                FUNCTION VOID log(RECORD pair p)
+                BEGIN
+                    WRITE(p.b);
+                END
+
+                FUNCTION VOID main()
+                BEGIN
+                    RECORD pair q;
+
+                    q.a := g.a;
+                    q.b += 1.5;
+                    log(q);
+                END
+            END
+        ";
+
+        let mut result = microc::ProgramParser::new().parse(program).unwrap();
+        let main = result.remove(0);
+
+        let mut visitor = ThreeAddressCodeVisitor;
+        let code_sequence: Vec<String> = visitor
+            .walk_ast(main)
+            .code_sequence
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+
+        // Fields of a local take up consecutive stack
+        // slots, and are pushed as separate arguments.
+        assert_eq!(
+            vec![
+                "LABEL main",
+                "LINK",
+                "STOREI g.a $L1",
+                "STOREF 1.5 $T1",
+                "ADDF $L2 $T1 $L2",
+                "PUSH $L1",
+                "PUSH $L2",
+                "JSR log",
+                "POP",
+                "POP",
+                "RET",
+            ],
+            code_sequence
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn passing_record_of_another_type_is_rejected() {
+        let program = r"
+            PROGRAM test
+            BEGIN
+                RECORD pair
+                BEGIN
+                    INT a, b;
+                END
+
+                RECORD twin
+                BEGIN
+                    INT a, b;
+                END
+
+                RECORD twin t;
+
+                FUNCTION VOID log(RECORD pair p)
+                BEGIN
+                    WRITE(p.a);
+                END
+
+                FUNCTION VOID main()
+                BEGIN
+                    log(t);
+                END
+            END
+        ";
+
+        assert!(microc::ProgramParser::new().parse(program).is_ok());
+        assert!(matches!(
+            SymbolTable::symbol_errors().as_slice(),
+            [CompileError::Type(diagnostic)]
+                if diagnostic.code == Code::MismatchedTypes
+        ));
+    }
+
+    #[test]
+    #[serial]
+    fn access_to_undeclared_record_field_is_rejected() {
+        let program = r"
+            PROGRAM test
+            BEGIN
+                RECORD pair
+                BEGIN
+                    INT a, b;
+                END
+
+                RECORD pair p;
+
+                FUNCTION VOID main()
+                BEGIN
+                    p.c := 1;
+                END
+            END
+        ";
+
+        assert!(microc::ProgramParser::new().parse(program).is_ok());
+        assert!(matches!(
+            SymbolTable::symbol_errors().as_slice(),
+            [CompileError::Semantic(diagnostic)]
+                if diagnostic.code == Code::InvalidRecord
+        ));
+    }
+
+    #[test]
+    #[serial]
+    fn convert_var_args_into_copy_in_and_copy_out() {
+        let program = r"
+            PROGRAM test
+            BEGIN
+                INT g;
+
+                FUNCTION VOID update(VAR INT x, FLOAT y, VAR FLOAT z)
+                BEGIN
+                    x := 1;
+                END
+
+                FUNCTION VOID main()
+                BEGIN
+                    FLOAT a[4];
+
+                    update(g, 2, a[g]);
+                END
+            END
+        ";
+
+        let mut result = microc::ProgramParser::new().parse(program).unwrap();
+        let main = result.remove(0);
+
+        let mut visitor = ThreeAddressCodeVisitor;
+        let code_sequence: Vec<String> = visitor
+            .walk_ast(main)
+            .code_sequence
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+
+        // The values of VAR parameters are popped back into the
+        // arguments, reusing the index of the array element.
+        assert_eq!(
+            vec![
+                "LABEL main",
+                "LINK",
+                "STOREF 2 $T2",
+                "STOREI g $T3",
+                "LOADF $L1[$T3] $T4",
+                "PUSH g",
+                "PUSH $T2",
+                "PUSH $T4",
+                "JSR update",
+                "POP $T5",
+                "STOREF $T5 $L1[$T3]",
+                "POP",
+                "POP $T6",
+                "STOREI $T6 g",
+                "RET",
+            ],
+            code_sequence
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn passing_expression_as_var_arg_is_rejected() {
+        let program = r"
+            PROGRAM test
+            BEGIN
+                INT g;
+
+                FUNCTION VOID update(VAR INT x)
+                BEGIN
+                    x := 1;
+                END
+
+                FUNCTION VOID main()
+                BEGIN
+                    update(g + 1);
+                END
+            END
+        ";
+
+        let mut result = microc::ProgramParser::new().parse(program).unwrap();
+
+        assert!(matches!(
+            type_check::check_program(&mut result).as_slice(),
+            [CompileError::Type(_)]
+        ));
+    }
+
+    #[test]
+    #[serial]
+    fn passing_int_as_var_float_arg_is_rejected() {
+        let program = r"
+            PROGRAM test
+            BEGIN
+                INT g;
+
+                FUNCTION VOID update(VAR FLOAT x)
+                BEGIN
+                    x := 1.5;
+                END
+
+                FUNCTION VOID main()
+                BEGIN
+                    update(g);
+                END
+            END
+        ";
+
+        let mut result = microc::ProgramParser::new().parse(program).unwrap();
+
+        assert!(matches!(
+            type_check::check_program(&mut result).as_slice(),
+            [CompileError::Type(_)]
+        ));
+    }
+
+    #[test]
+    #[serial]
+    fn parsed_nodes_record_their_source_spans() {
+        let program = r"PROGRAM test
+BEGIN
+    INT a;
+
+    FUNCTION VOID main()
+    BEGIN
+        a := a + 1;
+    END
+END";
+
+        let mut result = microc::ProgramParser::new().parse(program).unwrap();
+        let main = result.remove(0);
+        let line_map = LineMap::new(program);
+
+        assert_eq!(
+            LineCol { line: 5, col: 5 },
+            line_map.line_col(main.span().start)
+        );
+        assert_eq!(
+            LineCol { line: 8, col: 8 },
+            line_map.line_col(main.span().end)
+        );
+
+        let (symbol, body) = match main {
+            AstNode::Item(ast_node::Item::Function { symbol, body, .. }) => (symbol, body),
+            ast => panic!("Unexpected AST node: {:?}", ast),
+        };
+        let text = |span: Span| &program[span.start..span.end];
+
+        assert_eq!("FUNCTION VOID main()", text(symbol.span()));
+        assert_eq!("a := a + 1;", text(body[0].span));
+        match &body[0].kind {
+            ast_node::StmtKind::Assign(assignment) => {
+                assert_eq!("a", text(assignment.lhs.span));
+                assert_eq!("a + 1", text(assignment.rhs.span));
+            }
+            stmt => panic!("Unexpected statement: {:?}", stmt),
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn prototype_mismatch_points_at_both_declarations() {
+        let program = r"PROGRAM test
+BEGIN
+    FUNCTION INT f(INT a);
+
+    FUNCTION FLOAT f(INT a)
+    BEGIN
+        RETURN 1.0;
+    END
+END";
+
+        assert!(microc::ProgramParser::new().parse(program).is_ok());
+
+        let err = SymbolTable::symbol_errors().remove(0);
+        assert!(matches!(
+            err,
+            CompileError::Symbol(SymbolError::MismatchedFunctionPrototype(_), _)
+        ));
+
+        let diagnostic = err.diagnostic();
+        let text = |span: Span| &program[span.start..span.end];
+
+        assert_eq!(Code::PrototypeMismatch, diagnostic.code);
+        assert_eq!(
+            "definition of function `f` does not match its prototype",
+            diagnostic.message
+        );
+        assert_eq!("FUNCTION FLOAT f(INT a)", text(diagnostic.primary.span));
+        assert_eq!(1, diagnostic.secondary.len());
+        assert_eq!("FUNCTION INT f(INT a)", text(diagnostic.secondary[0].span));
+    }
+
+    #[test]
+    #[serial]
+    fn uncalled_undefined_prototype_is_a_warning() {
+        let program = r"
+            PROGRAM test
+            BEGIN
+                FUNCTION INT f();
+
+                FUNCTION VOID main()
+                BEGIN
+                END
+            END
+        ";
+
+        assert!(microc::ProgramParser::new().parse(program).is_ok());
+
+        let warnings = SymbolTable::warnings();
+        assert_eq!(1, warnings.len());
+        assert_eq!(Severity::Warning, warnings[0].severity);
+        assert_eq!(Code::UnusedPrototype, warnings[0].code);
+    }
+
+    #[test]
+    #[serial]
+    fn function_that_can_reach_its_end_without_returning_is_a_warning() {
+        let program = r"
+            PROGRAM test
+            BEGIN
+                FUNCTION INT f(INT a)
+                BEGIN
+                    IF (a > 0)
+                        RETURN a;
+                    FI
+                END
+
+                FUNCTION VOID main()
+                BEGIN
+                END
+            END
+        ";
+
+        assert!(microc::ProgramParser::new().parse(program).is_ok());
+
+        let warnings = SymbolTable::warnings();
+        assert_eq!(1, warnings.len());
+        assert_eq!(Severity::Warning, warnings[0].severity);
+        assert_eq!(Code::MissingReturn, warnings[0].code);
+    }
+
+    #[test]
+    #[serial]
+    fn function_that_returns_on_every_path_is_not_a_warning() {
+        let program = r"
+            PROGRAM test
+            BEGIN
+                FUNCTION INT f(INT a)
+                BEGIN
+                    IF (a > 0)
+                        RETURN a;
+                    ELSE
+                        DO
+                            a := a + 1;
+                        WHILE (a < 0);
+                    FI
+                    RETURN 0;
+                END
+
+                FUNCTION INT g(INT a)
+                BEGIN
+                    DO
+                        RETURN a;
+                    WHILE (TRUE);
+                END
+
+                FUNCTION VOID main()
+                BEGIN
+                END
+            END
+        ";
+
+        assert!(microc::ProgramParser::new().parse(program).is_ok());
+        assert!(SymbolTable::warnings().is_empty());
+    }
+
+    #[test]
+    #[serial]
+    fn parser_recovers_from_syntax_errors() {
+        let program = r"PROGRAM test
+BEGIN
+    INT a b;
+
+    FUNCTION VOID f(INT x y)
+    BEGIN
+    END
+
+    FUNCTION VOID main()
+    BEGIN
+        INT i;
+        i := 1 +;
+        FOR (i := 0; i < 3; i := i + 1)
+            i := 2 *
+        ROF
+        WRITE(i);
+    END
+END";
+
+        let mut result = microc::ProgramParser::new().parse(program).unwrap();
+        let line_map = LineMap::new(program);
+
+        let errors = SymbolTable::syntax_errors()
+            .into_iter()
+            .map(|error| match error {
+                CompileError::Syntax(diagnostic) => (
+                    diagnostic.code,
+                    line_map.line_col(diagnostic.primary.span.start),
+                ),
+                error => panic!("Unexpected error: {:?}", error),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                (Code::UnexpectedToken, LineCol { line: 3, col: 11 }),
+                (Code::UnexpectedToken, LineCol { line: 5, col: 27 }),
+                (Code::UnexpectedToken, LineCol { line: 12, col: 17 }),
+                (Code::UnexpectedToken, LineCol { line: 15, col: 9 }),
+            ],
+            errors
+        );
+
+        // The statements around the errors are still parsed
+        assert_eq!(1, result.len());
+        let body = match result.remove(0) {
+            AstNode::Item(ast_node::Item::Function { body, .. }) => body,
+            ast => panic!("Unexpected AST node: {:?}", ast),
+        };
+        assert!(matches!(
+            body.last().map(|stmt| &stmt.kind),
+            Some(ast_node::StmtKind::Write(_))
+        ));
     }
 }